@@ -1,6 +1,6 @@
 use substratekitties_runtime::{
-    AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, SudoConfig,
-    SystemConfig, WASM_BINARY,
+    AccountId, AuraConfig, BalancesConfig, GenesisConfig, GrandpaConfig, Signature, Substratekitties,
+    SudoConfig, SystemConfig, DOLLARS, WASM_BINARY,
 };
 use sc_service::ChainType;
 use sp_consensus_aura::sr25519::AuthorityId as AuraId;
@@ -145,6 +145,8 @@ fn testnet_genesis(
                 .iter()
                 .cloned()
                 .map(|k| (k, 1 << 60))
+                // The kitty treasury must exist to collect marketplace fees.
+                .chain(Some((Substratekitties::treasury_account(), DOLLARS)))
                 .collect(),
         }),
         pallet_aura: Some(AuraConfig {
@@ -0,0 +1,70 @@
+//! Battles between kitties, whose odds of winning are weighted by their power, and the
+//! tournaments that pit many kitties against each other.
+
+use codec::{Decode, Encode};
+use sp_core::RuntimeDebug;
+use sp_std::vec::Vec;
+
+/// An open challenge from one kitty to another, whose stake is reserved in the challenger's
+/// account
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Challenge<AccountId, Hash, Balance> {
+    pub challenger: AccountId,
+    /// The kitty challenged to battle.
+    pub opponent: Hash,
+    /// The amount each side stakes on the battle.
+    pub stake: Balance,
+    /// The opponent's user once they accept, whose matching stake is then reserved until the
+    /// battle is fought in the next block.
+    pub accepted_by: Option<AccountId>,
+}
+
+/// A kitty's battle history
+#[derive(Clone, Copy, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct BattleRecord {
+    pub wins: u32,
+    pub losses: u32,
+}
+
+/// A knockout tournament whose entry fees make up its prize pool
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Tournament<AccountId, Balance, BlockNumber> {
+    pub organizer: AccountId,
+    pub max_entrants: u32,
+    pub entry_fee: Balance,
+    /// The block at which the bracket is seeded and the first round played.
+    pub start: BlockNumber,
+}
+
+/// The state of a tournament's bracket, whose entries are kitties and the accounts that entered
+/// them
+#[derive(Clone, Default, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Bracket<Entry> {
+    /// The entries yet to play in the current round, or the registered entries before the
+    /// tournament starts.
+    pub round: Vec<Entry>,
+    /// The winners of the current round's matches so far.
+    pub advancing: Vec<Entry>,
+    /// The knocked out entries, in the order they were knocked out.
+    pub eliminated: Vec<Entry>,
+}
+
+/// Whether a kitty with `power` beats one with `other_power`.
+///
+/// A kitty wins with probability proportional to its share of the two kitties' total power, and
+/// two powerless kitties are evenly matched. The outcome is fully determined by `seed`.
+pub fn wins(power: u64, other_power: u64, seed: &[u8]) -> bool {
+    let mut bytes = [0u8; 8];
+    for (byte, seed) in bytes.iter_mut().zip(seed) {
+        *byte = *seed;
+    }
+    let roll = u64::from_le_bytes(bytes);
+
+    match power.checked_add(other_power) {
+        Some(0) => roll % 2 == 0,
+        Some(total) => roll % total < power,
+        // Powers this large only come from enormous boosts, so halving them costs no precision
+        // that matters.
+        None => roll % (power / 2 + other_power / 2) < power / 2,
+    }
+}
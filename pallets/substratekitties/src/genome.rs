@@ -0,0 +1,211 @@
+//! Decoding of kitty DNA into the traits drawn by the front-end's kitty avatar, and the
+//! genetics that pass those traits from parents to offspring.
+//!
+//! Every trait slot carries two alleles. The dominant allele is the hex digit of the DNA that
+//! the avatar draws; the recessive allele is hidden further along the DNA and only surfaces in
+//! offspring.
+
+use codec::{Decode, Encode};
+use sp_core::RuntimeDebug;
+use sp_runtime::Perbill;
+
+/// The number of body variants.
+pub const BODIES: u8 = 15;
+/// The number of eye variants.
+pub const EYES: u8 = 15;
+/// The number of accessory variants.
+pub const ACCESSORIES: u8 = 20;
+/// The number of fur pattern variants.
+pub const PATTERNS: u8 = 10;
+/// The number of mouth variants.
+pub const MOUTHS: u8 = 10;
+/// The number of snack variants.
+pub const SNACKS: u8 = 2;
+
+/// The hex digit of the DNA that marks a kitty as having a snack.
+const SNACK_MARKER: u8 = 8;
+
+/// The position of the first recessive allele among the DNA's hex digits.
+const RECESSIVE_OFFSET: usize = 32;
+
+/// The largest allele a hex digit of the DNA can hold.
+const MAX_ALLELE: u8 = 0x0f;
+
+/// A trait slot that carries a dominant and a recessive allele
+///
+/// The snack slot's allele is the marker that decides whether a kitty has a snack at all.
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum Slot {
+    Body,
+    Eyes,
+    Accessory,
+    Pattern,
+    Mouth,
+    Snack,
+}
+
+impl Slot {
+    pub const ALL: [Slot; 6] = [
+        Slot::Body,
+        Slot::Eyes,
+        Slot::Accessory,
+        Slot::Pattern,
+        Slot::Mouth,
+        Slot::Snack,
+    ];
+
+    fn dominant_index(self) -> usize {
+        self as usize
+    }
+
+    fn recessive_index(self) -> usize {
+        RECESSIVE_OFFSET + self as usize
+    }
+}
+
+/// A pair of alleles that may mutate into a rare allele when an offspring inherits both
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Mutation {
+    pub slot: Slot,
+    /// The alleles, in either order, that must meet for the mutation to occur.
+    pub alleles: (u8, u8),
+    /// The dominant allele of an offspring in which the mutation occurs.
+    pub result: u8,
+    pub chance: Perbill,
+}
+
+impl Mutation {
+    /// Whether the mutation's alleles and result each fit in a hex digit of the DNA.
+    pub fn is_valid(&self) -> bool {
+        self.alleles.0 <= MAX_ALLELE && self.alleles.1 <= MAX_ALLELE && self.result <= MAX_ALLELE
+    }
+}
+
+/// The traits encoded in a kitty's DNA
+///
+/// Each trait is the index of one of its variants.
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default, RuntimeDebug)]
+pub struct KittyGenome {
+    pub body: u8,
+    pub eyes: u8,
+    pub accessory: u8,
+    pub pattern: u8,
+    pub mouth: u8,
+    pub snack: Option<u8>,
+    pub power: u8,
+}
+
+impl KittyGenome {
+    /// Decode a kitty's DNA the same way the front-end's kitty avatar does.
+    ///
+    /// Traits are read from the leading hex digits of the DNA. Power is the hex digit at the
+    /// position given by the DNA's final hex digit.
+    pub fn from_dna(dna: &[u8]) -> Self {
+        let variant = |index, variants| nibble(dna, index) % variants;
+        let last = (dna.len() * 2).saturating_sub(1);
+
+        KittyGenome {
+            body: variant(0, BODIES),
+            eyes: variant(1, EYES),
+            accessory: variant(2, ACCESSORIES),
+            pattern: variant(3, PATTERNS),
+            mouth: variant(4, MOUTHS),
+            snack: if nibble(dna, 5) == SNACK_MARKER {
+                Some(variant(6, SNACKS))
+            } else {
+                None
+            },
+            power: nibble(dna, nibble(dna, last) as usize),
+        }
+    }
+}
+
+/// The dominant and recessive alleles of a slot.
+pub fn alleles(dna: &[u8], slot: Slot) -> (u8, u8) {
+    (nibble(dna, slot.dominant_index()), nibble(dna, slot.recessive_index()))
+}
+
+/// A score for how rare a kitty's traits are.
+///
+/// Every slot whose dominant allele is the result of one of `mutations` scores the inverse of
+/// that mutation's chance, so rarer mutations score higher.
+pub fn rarity(dna: &[u8], mutations: &[Mutation]) -> u32 {
+    Slot::ALL
+        .iter()
+        .filter_map(|slot| {
+            let (dominant, _) = alleles(dna, *slot);
+            mutations.iter().find(|mutation| mutation.slot == *slot && mutation.result == dominant)
+        })
+        .map(|mutation| 1_000_000_000 / mutation.chance.deconstruct().max(1))
+        .fold(0, u32::saturating_add)
+}
+
+/// Cross two parents' DNA into an offspring's DNA, which must be the same length.
+///
+/// For every slot, each parent passes on one of its two alleles and the offspring's dominant
+/// allele is picked from the two, unless the pair mutates. Every other byte of the offspring's
+/// DNA comes from one parent or the other. The outcome is fully determined by `seed`.
+///
+/// Mutations that are not valid are ignored, since their result would spill into the
+/// neighbouring hex digit.
+pub fn cross(dna: &[u8], other: &[u8], seed: &[u8], mutations: &[Mutation], child: &mut [u8]) {
+    for (i, byte) in child.iter_mut().enumerate() {
+        let parent = if seed.get(i).copied().unwrap_or_default() % 2 == 0 {
+            dna
+        } else {
+            other
+        };
+        *byte = parent.get(i).copied().unwrap_or_default();
+    }
+
+    for slot in Slot::ALL.iter() {
+        let draw = sp_io::hashing::blake2_256(&(seed, *slot).encode());
+        let pick = |dna, bit| {
+            let (dominant, recessive) = alleles(dna, *slot);
+            if draw[0] & bit == 0 { dominant } else { recessive }
+        };
+        let (first, second) = (pick(dna, 0b001), pick(other, 0b010));
+        let (mut dominant, recessive) = if draw[0] & 0b100 == 0 {
+            (first, second)
+        } else {
+            (second, first)
+        };
+
+        let roll = u32::from_le_bytes([draw[1], draw[2], draw[3], draw[4]]);
+        let mutation = mutations.iter().find(|mutation| {
+            mutation.is_valid()
+                && mutation.slot == *slot
+                && (mutation.alleles == (dominant, recessive)
+                    || mutation.alleles == (recessive, dominant))
+        });
+        if let Some(mutation) = mutation {
+            if Perbill::from_parts(roll % 1_000_000_000) < mutation.chance {
+                dominant = mutation.result;
+            }
+        }
+
+        set_nibble(child, slot.dominant_index(), dominant);
+        set_nibble(child, slot.recessive_index(), recessive);
+    }
+}
+
+/// The hex digit of the DNA at `index`, most significant first.
+pub(crate) fn nibble(dna: &[u8], index: usize) -> u8 {
+    let byte = dna.get(index / 2).copied().unwrap_or_default();
+    if index % 2 == 0 {
+        byte >> 4
+    } else {
+        byte & 0x0f
+    }
+}
+
+/// Set the hex digit of the DNA at `index`, most significant first.
+fn set_nibble(dna: &mut [u8], index: usize, value: u8) {
+    if let Some(byte) = dna.get_mut(index / 2) {
+        *byte = if index % 2 == 0 {
+            (*byte & 0x0f) | ((value & 0x0f) << 4)
+        } else {
+            (*byte & 0xf0) | (value & 0x0f)
+        };
+    }
+}
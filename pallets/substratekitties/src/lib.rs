@@ -2,15 +2,31 @@
 
 use codec::{Decode, Encode};
 use frame_support::{
-    decl_error, decl_event, decl_module, decl_storage, dispatch,
-    traits::{Currency, Get, LockIdentifier, LockableCurrency, Randomness, Time, WithdrawReason},
+    decl_error, decl_event, decl_module, decl_storage, dispatch, ensure,
+    traits::{
+        BalanceStatus, Currency, ExistenceRequirement, Get, LockIdentifier, LockableCurrency,
+        Randomness, ReservableCurrency, Time, WithdrawReason, WithdrawReasons,
+    },
+    transactional,
+    weights::Weight,
+    IterableStorageDoubleMap,
 };
-use frame_system::ensure_signed;
+use frame_system::{ensure_root, ensure_signed};
 use sp_core::RuntimeDebug;
+use sp_runtime::{
+    traits::{AccountIdConversion, Hash, One, SaturatedConversion, Saturating, Zero},
+    ModuleId, Perbill,
+};
 use sp_std::vec::Vec;
 
 use pallet_commodities::nft::UniqueAssets;
 
+mod battle;
+mod genome;
+
+pub use battle::{BattleRecord, Bracket, Challenge, Tournament};
+pub use genome::{KittyGenome, Mutation, Slot};
+
 #[cfg(test)]
 mod mock;
 
@@ -32,10 +48,168 @@ pub struct KittyMetadata {
     name: Vec<u8>,
 }
 
+/// A kitty's place in its family tree
+#[derive(Clone, Eq, PartialEq, Encode, Decode, Default, RuntimeDebug)]
+pub struct Lineage<Hash> {
+    /// The kitty's parents, or `None` if it was conjured.
+    parents: Option<(Hash, Hash)>,
+    /// The number of generations between the kitty and its conjured ancestors.
+    generation: u32,
+}
+
+/// The kitty or owner a breeding intent is addressed to
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum FlirtTarget<AccountId, Hash> {
+    Kitty(Hash),
+    Owner(AccountId),
+}
+
+/// A kitty's posted intent to breed
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct BreedingIntent<AccountId, Hash, BlockNumber> {
+    /// The kitty's user when the intent was posted.
+    poster: AccountId,
+    expires: BlockNumber,
+    /// The intent's position in the queue of tasks due at `expires`.
+    position: u32,
+    target: Option<FlirtTarget<AccountId, Hash>>,
+}
+
+/// How the price of a listed kitty is set
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum Pricing<Balance, BlockNumber> {
+    Fixed(Balance),
+    /// A price that declines linearly from `start_price` at block `start` to `end_price` over
+    /// `duration` blocks, then stays at `end_price`.
+    Declining {
+        start_price: Balance,
+        end_price: Balance,
+        start: BlockNumber,
+        duration: BlockNumber,
+    },
+}
+
+/// A kitty's listing for sale
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Listing<AccountId, Balance, BlockNumber> {
+    seller: AccountId,
+    pricing: Pricing<Balance, BlockNumber>,
+}
+
+/// An English auction for a kitty
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Auction<AccountId, Balance, BlockNumber> {
+    seller: AccountId,
+    /// The lowest acceptable bid.
+    reserve: Balance,
+    /// The least a bid must exceed the high bid by.
+    min_increment: Balance,
+    /// The block at which bidding closes and the auction settles.
+    end: BlockNumber,
+    /// The high bidder and their bid, which is reserved in their account.
+    high_bid: Option<(AccountId, Balance)>,
+}
+
+/// A standing offer to buy a kitty, whose amount is reserved in the bidder's account
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Offer<Balance, BlockNumber> {
+    amount: Balance,
+    expires: BlockNumber,
+}
+
+/// A kitty lent to another account, who uses it in the owner's place until the rental ends
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub struct Rental<AccountId, BlockNumber> {
+    owner: AccountId,
+    user: AccountId,
+    /// The block at which the kitty returns to its owner.
+    end: BlockNumber,
+}
+
+/// A queue of work that falls due at a block
+#[derive(Clone, Copy, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum Queue {
+    Intents,
+    Offers,
+    Auctions,
+    Rentals,
+    Battles,
+    Tournaments,
+}
+
+/// Work that falls due at a block
+#[derive(Clone, Eq, PartialEq, Encode, Decode, RuntimeDebug)]
+pub enum Task<AccountId, Hash> {
+    /// Remove a kitty's breeding intent if it has expired.
+    ExpireIntent(Hash),
+    /// Refund a bidder's offer on a kitty if it has expired.
+    ExpireOffer(Hash, AccountId),
+    /// Settle a kitty's auction if it has ended.
+    SettleAuction(Hash),
+    /// Return a rented kitty to its owner if the rental has ended.
+    EndRental(Hash),
+    /// Fight the accepted challenge of a kitty.
+    ResolveBattle(Hash),
+    /// Seed a tournament's bracket.
+    StartTournament(u32),
+}
+
+impl<AccountId, Hash> Task<AccountId, Hash> {
+    /// The queue the task waits in.
+    fn queue(&self) -> Queue {
+        match self {
+            Task::ExpireIntent(_) => Queue::Intents,
+            Task::ExpireOffer(..) => Queue::Offers,
+            Task::SettleAuction(_) => Queue::Auctions,
+            Task::EndRental(_) => Queue::Rentals,
+            Task::ResolveBattle(_) => Queue::Battles,
+            Task::StartTournament(_) => Queue::Tournaments,
+        }
+    }
+}
+
+/// Where a buyer's payment for a kitty comes from
+enum Funds {
+    Free,
+    Reserved,
+}
+
 type BalanceOf<T> =
     <<T as Trait>::Currency as Currency<<T as frame_system::Trait>::AccountId>>::Balance;
-type KittyInfoOf<T> =
-    KittyInfo<<T as frame_system::Trait>::Hash, <<T as Trait>::Time as Time>::Moment>;
+type MomentOf<T> = <<T as Trait>::Time as Time>::Moment;
+type KittyInfoOf<T> = KittyInfo<<T as frame_system::Trait>::Hash, MomentOf<T>>;
+type ListingOf<T> = Listing<
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Trait>::BlockNumber,
+>;
+type AuctionOf<T> = Auction<
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Trait>::BlockNumber,
+>;
+type RentalOf<T> =
+    Rental<<T as frame_system::Trait>::AccountId, <T as frame_system::Trait>::BlockNumber>;
+type FlirtTargetOf<T> =
+    FlirtTarget<<T as frame_system::Trait>::AccountId, <T as frame_system::Trait>::Hash>;
+type ChallengeOf<T> = Challenge<
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::Hash,
+    BalanceOf<T>,
+>;
+type TournamentOf<T> = Tournament<
+    <T as frame_system::Trait>::AccountId,
+    BalanceOf<T>,
+    <T as frame_system::Trait>::BlockNumber,
+>;
+type BracketOf<T> =
+    Bracket<(<T as frame_system::Trait>::Hash, <T as frame_system::Trait>::AccountId)>;
+type TaskOf<T> = Task<<T as frame_system::Trait>::AccountId, <T as frame_system::Trait>::Hash>;
+type BreedingIntentOf<T> = BreedingIntent<
+    <T as frame_system::Trait>::AccountId,
+    <T as frame_system::Trait>::Hash,
+    <T as frame_system::Trait>::BlockNumber,
+>;
 
 pub trait Trait: frame_system::Trait {
     type Kitties: pallet_commodities::nft::UniqueAssets<
@@ -45,14 +219,135 @@ pub trait Trait: frame_system::Trait {
     >;
     type Time: frame_support::traits::Time;
     type Randomness: frame_support::traits::Randomness<Self::Hash>;
-    type Currency: frame_support::traits::LockableCurrency<Self::AccountId>;
+    type Currency: frame_support::traits::LockableCurrency<Self::AccountId>
+        + frame_support::traits::ReservableCurrency<Self::AccountId>;
     type BasePrice: Get<BalanceOf<Self>>;
+    /// The number of blocks a breeding intent stays open.
+    type FlirtPeriod: Get<Self::BlockNumber>;
+    /// The maximum number of expired breeding intents to prune in a single block.
+    type MaxIntentPrunes: Get<u32>;
+    /// The maximum number of generations a pedigree query walks back.
+    type MaxPedigreeDepth: Get<u32>;
+    /// The number of generations within which kitties that share an ancestor may not breed.
+    /// Parents, children and siblings may never breed. Capped at `MaxPedigreeDepth`.
+    type MaxInbreedingDepth: Get<u32>;
+    /// The number of blocks a kitty rests after breeding, indexed by its generation. Generations
+    /// past the end of the schedule use its last entry.
+    type CooldownSchedule: Get<Vec<Self::BlockNumber>>;
+    /// How old a kitty must be before it can breed.
+    type MinBreedingAge: Get<<Self::Time as Time>::Moment>;
+    /// The allele pairs that may mutate into rare alleles when bred together.
+    type Mutations: Get<Vec<Mutation>>;
+    /// The maximum number of ended auctions to settle in a single block.
+    type MaxAuctionSettlements: Get<u32>;
+    /// The number of blocks an offer to buy a kitty stays open.
+    type OfferPeriod: Get<Self::BlockNumber>;
+    /// The maximum number of expired offers to refund in a single block.
+    type MaxOfferRefunds: Get<u32>;
+    /// The maximum number of open offers on a single kitty.
+    type MaxOffersPerKitty: Get<u32>;
+    /// The portion of every sale price paid to the kitty's creator.
+    type RoyaltyPercent: Get<Perbill>;
+    /// The identifier of the treasury that collects marketplace fees.
+    type TreasuryId: Get<ModuleId>;
+    /// The maximum number of ended rentals to return in a single block.
+    type MaxRentalReturns: Get<u32>;
+    /// The maximum number of accepted battles to fight in a single block.
+    type MaxBattles: Get<u32>;
+    /// The number of kitties on each leaderboard. Twice as many candidates are tracked so that
+    /// leaders that drop out can be replaced.
+    type LeaderboardSize: Get<u32>;
+    /// The identifier from which tournaments' prize pool accounts are derived.
+    type TournamentId: Get<ModuleId>;
+    /// The most entrants a tournament may allow.
+    type MaxTournamentSize: Get<u32>;
+    /// The most tournaments that may be open at once, whether taking entries or under way.
+    type MaxRunningTournaments: Get<u32>;
+    /// The maximum number of tournaments to start, and of tournament matches to play, in a single
+    /// block.
+    type MaxTournamentMatches: Get<u32>;
+    /// The portions of a tournament's prize pool paid to its top finishers, champion first. The
+    /// champion also takes whatever the other portions leave over.
+    type TournamentPrizes: Get<Vec<Perbill>>;
     type Event: From<Event<Self>> + Into<<Self as frame_system::Trait>::Event>;
 }
 
 decl_storage! {
     trait Store for Module<T: Trait> as Substratekitties {
         MetadataForKitty get(fn metadata_for_kitty): map hasher(identity) T::Hash => KittyMetadata;
+        /// Funds locked against a kitty on top of the base price, which serve as a power boost.
+        BoostForKitty get(fn boost_for_kitty): map hasher(identity) T::Hash => BalanceOf<T>;
+        /// The account that conjured or bred each kitty, which earns royalties on its sales.
+        CreatorOf get(fn creator_of): map hasher(identity) T::Hash => Option<T::AccountId>;
+        /// Each kitty's parents and generation. Kept after a kitty is released so its
+        /// descendants' pedigrees stay complete.
+        LineageForKitty get(fn lineage_for_kitty): map hasher(identity) T::Hash => Lineage<T::Hash>;
+        /// The kitties bred from each kitty, keyed by parent then child, so a prolific parent's
+        /// lineage stays small. Kept after a kitty is released, like its lineage.
+        Children: double_map hasher(identity) T::Hash, hasher(identity) T::Hash => ();
+        /// The total funds locked for an account: the base price of every kitty it owns plus their boosts.
+        LockedForAccount get(fn locked_for_account): map hasher(blake2_128_concat) T::AccountId => BalanceOf<T>;
+        /// The block from which a kitty that has bred may breed again.
+        ReadyAt get(fn ready_at): map hasher(identity) T::Hash => T::BlockNumber;
+        /// Open intents to breed, by kitty.
+        BreedingIntents get(fn breeding_intent): map hasher(identity) T::Hash => Option<BreedingIntentOf<T>>;
+        /// Kitties listed for sale, by kitty.
+        Listings get(fn listing): map hasher(identity) T::Hash => Option<ListingOf<T>>;
+        /// Kitties offered as sires, by kitty, along with their owner and siring fee.
+        SireListings get(fn sire_listing): map hasher(identity) T::Hash => Option<(T::AccountId, BalanceOf<T>)>;
+        /// Offers to buy kitties, by kitty and bidder.
+        Offers get(fn offer): double_map hasher(identity) T::Hash, hasher(blake2_128_concat) T::AccountId
+            => Option<Offer<BalanceOf<T>, T::BlockNumber>>;
+        /// The number of open offers on a kitty.
+        OfferCount get(fn offer_count): map hasher(identity) T::Hash => u32;
+        /// Kitties under auction, by kitty.
+        Auctions get(fn auction): map hasher(identity) T::Hash => Option<AuctionOf<T>>;
+        /// Kitties offered for rent, by kitty, along with their owner, price and rental duration.
+        RentalOffers get(fn rental_offer):
+            map hasher(identity) T::Hash => Option<(T::AccountId, BalanceOf<T>, T::BlockNumber)>;
+        /// Kitties out on rental, by kitty.
+        Rentals get(fn rental): map hasher(identity) T::Hash => Option<RentalOf<T>>;
+        /// Open challenges to battle, by challenging kitty.
+        Challenges get(fn challenge): map hasher(identity) T::Hash => Option<ChallengeOf<T>>;
+        /// Kitties' battle histories, by kitty.
+        BattleRecords get(fn battle_record): map hasher(identity) T::Hash => BattleRecord;
+        /// The account approved to transfer a kitty, by kitty, along with the owner who approved it.
+        ///
+        /// An approval only holds while that owner still owns the kitty.
+        Approvals get(fn approval): map hasher(identity) T::Hash => Option<(T::AccountId, T::AccountId)>;
+        /// Whether an operator may transfer and approve all of an owner's kitties, by owner and operator.
+        Operators get(fn is_operator): double_map hasher(blake2_128_concat) T::AccountId,
+            hasher(blake2_128_concat) T::AccountId => bool;
+        /// The portion of every sale price paid to the treasury. Set by governance.
+        MarketFee get(fn market_fee): Perbill;
+        /// Tournaments that have not finished yet, by id.
+        Tournaments get(fn tournament): map hasher(twox_64_concat) u32 => Option<TournamentOf<T>>;
+        /// The brackets of tournaments that have not finished yet, by tournament.
+        Brackets get(fn bracket): map hasher(twox_64_concat) u32 => BracketOf<T>;
+        /// The tournaments whose matches are under way.
+        RunningTournaments get(fn running_tournaments): Vec<u32>;
+        /// The number of tournaments that have been created and have not finished yet.
+        OpenTournaments get(fn open_tournaments): u32;
+        /// The id of the next tournament to be created.
+        NextTournamentId get(fn next_tournament_id): u32;
+        /// The candidates for the power leaderboard and their power, most powerful first. Up to
+        /// twice `LeaderboardSize` kitties are kept, of which the first `LeaderboardSize` lead.
+        PowerLeaders: Vec<(T::Hash, u64)>;
+        /// The candidates for the rarity leaderboard and their rarity score, rarest first.
+        RarityLeaders: Vec<(T::Hash, u32)>;
+        /// The candidates for the age leaderboard and their date of birth, oldest first.
+        AgeLeaders: Vec<(T::Hash, MomentOf<T>)>;
+        /// The work that falls due at a block, by queue and block, then by position in the
+        /// block's queue.
+        Tasks: double_map hasher(twox_64_concat) (Queue, T::BlockNumber), hasher(twox_64_concat) u32
+            => Option<TaskOf<T>>;
+        /// The position of the first unfinished task and the next free position in a block's
+        /// queue, by queue and block.
+        TaskBounds: map hasher(twox_64_concat) (Queue, T::BlockNumber) => (u32, u32);
+        /// The earliest block whose tasks did not all fit in the work allowed per block, by queue.
+        IncompleteSince: map hasher(twox_64_concat) Queue => Option<T::BlockNumber>;
+        /// Incremented for every random seed so seeds drawn in the same block differ.
+        Nonce get(fn nonce): u64;
     }
 }
 
@@ -61,14 +356,179 @@ decl_event!(
     where
         KittyId = <T as frame_system::Trait>::Hash,
         AccountId = <T as frame_system::Trait>::AccountId,
+        Balance = BalanceOf<T>,
+        BlockNumber = <T as frame_system::Trait>::BlockNumber,
     {
         Conjured(KittyId, AccountId),
+        /// A kitty's power was boosted by locking more funds. [kitty, owner, amount]
+        Boosted(KittyId, AccountId, Balance),
+        /// Part or all of a kitty's power boost was unlocked. [kitty, owner, amount]
+        Recouped(KittyId, AccountId, Balance),
+        /// A kitty and the funds locked against it moved to a new owner. [kitty, from, to]
+        Transferred(KittyId, AccountId, AccountId),
+        /// An account was approved to transfer a kitty, or the approval was cleared. [kitty, owner, spender]
+        Approved(KittyId, AccountId, Option<AccountId>),
+        /// An operator was approved for, or barred from, all of an owner's kitties. [owner, operator, approved]
+        ApprovedForAll(AccountId, AccountId, bool),
+        /// A kitty posted an intent to breed. [kitty, owner, expires]
+        Flirted(KittyId, AccountId, BlockNumber),
+        /// A kitty's intent to breed was cancelled. [kitty]
+        FlirtCancelled(KittyId),
+        /// Two kitties bred an offspring. [child, parent, partner, owner]
+        Bred(KittyId, KittyId, KittyId, AccountId),
+        /// A kitty was listed for sale or repriced. [kitty, seller, price]
+        Listed(KittyId, AccountId, Balance),
+        /// A kitty was listed at a declining price. [kitty, seller, start price, end price, duration]
+        ListedDeclining(KittyId, AccountId, Balance, Balance, BlockNumber),
+        /// A kitty's listing was cancelled. [kitty]
+        Delisted(KittyId),
+        /// A kitty was sold. [kitty, seller, buyer, price, marketplace fee]
+        Sold(KittyId, AccountId, AccountId, Balance, Balance),
+        /// The marketplace fee was changed. [fee]
+        MarketFeeSet(Perbill),
+        /// A kitty was offered as a sire for a fee. [kitty, owner, fee]
+        SireOffered(KittyId, AccountId, Balance),
+        /// A kitty's offer as a sire was withdrawn. [kitty]
+        SireWithdrawn(KittyId),
+        /// A siring fee was paid to breed with a sire. [sire, payer, fee]
+        SireHired(KittyId, AccountId, Balance),
+        /// An offer to buy a kitty was made or replaced. [kitty, bidder, amount, expires]
+        OfferMade(KittyId, AccountId, Balance, BlockNumber),
+        /// An offer to buy a kitty was withdrawn, expired or outbid and its funds were returned.
+        /// [kitty, bidder]
+        OfferWithdrawn(KittyId, AccountId),
+        /// A kitty was put up for auction. [kitty, seller, reserve, end]
+        AuctionStarted(KittyId, AccountId, Balance, BlockNumber),
+        /// A bid became the high bid in a kitty's auction. [kitty, bidder, amount]
+        BidPlaced(KittyId, AccountId, Balance),
+        /// A kitty's auction was cancelled by its seller. [kitty]
+        AuctionCancelled(KittyId),
+        /// A kitty's auction ended without a sale. [kitty]
+        AuctionUnsold(KittyId),
+        /// A royalty from a kitty's sale was paid to its creator. [kitty, creator, amount]
+        RoyaltyPaid(KittyId, AccountId, Balance),
+        /// A kitty was offered for rent. [kitty, owner, price, duration]
+        RentalOffered(KittyId, AccountId, Balance, BlockNumber),
+        /// A kitty's offer for rent was withdrawn. [kitty]
+        RentalWithdrawn(KittyId),
+        /// A kitty was rented out until a block. [kitty, owner, user, end]
+        Rented(KittyId, AccountId, AccountId, BlockNumber),
+        /// A rented kitty returned to its owner. [kitty, owner, user]
+        Returned(KittyId, AccountId, AccountId),
+        /// A kitty challenged another to battle. [kitty, opponent, challenger, stake]
+        Challenged(KittyId, KittyId, AccountId, Balance),
+        /// A challenge to battle was cancelled. [kitty]
+        ChallengeCancelled(KittyId),
+        /// A challenge to battle was accepted, to be fought in the next block.
+        /// [kitty, opponent, opponent's user]
+        ChallengeAccepted(KittyId, KittyId, AccountId),
+        /// A kitty won a battle and its user took both stakes. [winner, loser, winner's user, stake]
+        Battled(KittyId, KittyId, AccountId, Balance),
+        /// A tournament was created. [tournament, organizer, max entrants, entry fee, start]
+        TournamentCreated(u32, AccountId, u32, Balance, BlockNumber),
+        /// A kitty was entered into a tournament. [tournament, kitty, entrant]
+        TournamentEntered(u32, KittyId, AccountId),
+        /// A tournament's bracket was seeded and its first round begun. [tournament, entrants]
+        TournamentStarted(u32, u32),
+        /// A tournament was called off and its entry fees refunded. [tournament]
+        TournamentCancelled(u32),
+        /// An entry fee could not be refunded and stays in the prize pool. [tournament, kitty, entrant]
+        RefundFailed(u32, KittyId, AccountId),
+        /// A kitty won a tournament match. [tournament, winner, loser]
+        MatchWon(u32, KittyId, KittyId),
+        /// A prize was paid to a tournament's finisher. [tournament, kitty, entrant, place, amount]
+        PrizePaid(u32, KittyId, AccountId, u32, Balance),
+        /// A kitty was burned and the funds locked against it were released. [kitty, owner]
+        Released(KittyId, AccountId),
     }
 );
 
 decl_error! {
     pub enum Error for Module<T: Trait> {
         KittyConjureFailure,
+        /// The sender does not own the kitty.
+        NotKittyOwner,
+        /// The amount must be non-zero.
+        ZeroAmount,
+        /// The kitty does not have a power boost.
+        NoBoost,
+        /// The sender's free balance cannot cover the funds to lock.
+        InsufficientBalance,
+        /// The kitty does not have an open intent to breed.
+        NoBreedingIntent,
+        /// The partner's intent to breed is addressed to a different kitty or owner.
+        NotFlirtTarget,
+        /// A kitty cannot breed with itself.
+        CannotBreedWithSelf,
+        /// The kitty does not exist.
+        UnknownKitty,
+        /// The kitty bred too recently to breed again.
+        KittyCoolingDown,
+        /// The kitty is too young to breed.
+        KittyTooYoung,
+        /// The kitties are too closely related to breed.
+        KittiesRelated,
+        /// The kitty is not listed for sale.
+        NotListed,
+        /// The kitty's price is higher than the buyer is willing to pay.
+        PriceTooHigh,
+        /// An account cannot buy its own kitty.
+        CannotBuyOwnKitty,
+        /// The duration must be non-zero.
+        ZeroDuration,
+        /// The kitty is under auction.
+        KittyInAuction,
+        /// The kitty is not under auction.
+        NotInAuction,
+        /// The auction's bidding has closed.
+        AuctionEnded,
+        /// The auction's bidding has not closed yet.
+        AuctionNotEnded,
+        /// The bid is below the reserve or does not beat the high bid by the minimum increment.
+        BidTooLow,
+        /// An auction cannot be cancelled once it has bids.
+        AuctionHasBids,
+        /// The kitty is not offered as a sire.
+        NotOfferedAsSire,
+        /// The bidder has no open offer for the kitty.
+        NoOffer,
+        /// The offer is lower than the owner is willing to accept, than the existential deposit, or
+        /// than every open offer on a kitty that has the most open offers allowed.
+        OfferTooLow,
+        /// A declining price cannot end higher than it starts.
+        PriceIncreases,
+        /// The sender is neither the kitty's owner, its approved spender nor an operator for its owner.
+        NotApproved,
+        /// The marketplace fee and creator royalty together exceed the sale price.
+        FeeTooHigh,
+        /// The kitty is out on rental.
+        KittyRented,
+        /// The kitty is not offered for rent.
+        NotForRent,
+        /// A kitty cannot battle a kitty with the same user.
+        CannotBattleSelf,
+        /// The kitty has no open challenge to battle.
+        NoChallenge,
+        /// The challenge has been accepted and its battle is yet to be fought.
+        ChallengeAccepted,
+        /// The tournament does not exist or has finished.
+        UnknownTournament,
+        /// The tournament has already started.
+        TournamentStarted,
+        /// The tournament has no room for more entrants.
+        TournamentFull,
+        /// The kitty is already entered in the tournament.
+        AlreadyEntered,
+        /// The sender did not organize the tournament.
+        NotOrganizer,
+        /// A tournament must start in a future block.
+        StartInPast,
+        /// A tournament must allow between two and `MaxTournamentSize` entrants.
+        BadTournamentSize,
+        /// A tournament's entry fee must be at least the existential deposit.
+        EntryFeeTooLow,
+        /// `MaxRunningTournaments` tournaments are already open.
+        TooManyTournaments,
     }
 }
 
@@ -77,51 +537,1571 @@ decl_module! {
         type Error = Error<T>;
         fn deposit_event() = default;
 
+        const BasePrice: BalanceOf<T> = T::BasePrice::get();
+        const FlirtPeriod: T::BlockNumber = T::FlirtPeriod::get();
+
+        fn on_initialize(now: T::BlockNumber) -> Weight {
+            let pruned = Self::service(Queue::Intents, now, T::MaxIntentPrunes::get()) as Weight;
+            let settled = Self::service(Queue::Auctions, now, T::MaxAuctionSettlements::get()) as Weight;
+            let refunded = Self::service(Queue::Offers, now, T::MaxOfferRefunds::get()) as Weight;
+            let returned = Self::service(Queue::Rentals, now, T::MaxRentalReturns::get()) as Weight;
+            let fought = Self::service(Queue::Battles, now, T::MaxBattles::get()) as Weight;
+            let started = Self::service(Queue::Tournaments, now, T::MaxTournamentMatches::get()) as Weight;
+            let played = Self::play_matches() as Weight;
+            let operations = pruned + 8 * settled + 2 * refunded + 3 * returned + 8 * fought
+                + T::MaxTournamentSize::get() as Weight * started + 6 * played + 6;
+            T::DbWeight::get().reads_writes(operations, operations)
+        }
+
+        fn integrity_test() {
+            assert!(
+                T::Mutations::get().iter().all(Mutation::is_valid),
+                "every mutation's alleles and result must fit in a hex digit of the DNA",
+            );
+        }
+
         /// Reserve funds from the sender's account before conjuring them a kitty.
         ///
         /// The dispatch origin for this call must be Signed.
         #[weight = 10_000]
+        #[transactional]
         pub fn conjure(origin, name: Vec<u8>) -> dispatch::DispatchResult {
             let who = ensure_signed(origin)?;
-            T::Currency::set_lock(MODULE_ID, &who, T::BasePrice::get(), WithdrawReason::Fee | WithdrawReason::Reserve);
-            match T::Kitties::mint(&who, KittyInfo{dob: T::Time::now(), dna: T::Randomness::random(&MODULE_ID)}) {
+            Self::lock(&who, T::BasePrice::get())?;
+            match T::Kitties::mint(&who, KittyInfo{dob: T::Time::now(), dna: Self::random_seed(&who)}) {
                 Ok(id) => {
                     MetadataForKitty::<T>::insert(id, KittyMetadata{name: name});
+                    LineageForKitty::<T>::insert(id, Lineage::default());
+                    CreatorOf::<T>::insert(id, &who);
+                    Self::update_leaderboards(&id);
                     Self::deposit_event(RawEvent::Conjured(id, who));
                 },
                 Err(err) => Err(err)?
             }
 
-            // TODO: allow senders to supply extra funds to lock, which will serve as a power boost
+            Ok(())
+        }
+
+        /// Power up a kitty by locking more of the owner's funds against it.
+        ///
+        /// The boost increases the kitty's power without altering its DNA. A kitty's boost cannot
+        /// change while it is under auction or out on rental.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        pub fn boost(origin, kitty_id: T::Hash, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+            ensure!(!Auctions::<T>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+            ensure!(!Rentals::<T>::contains_key(kitty_id), Error::<T>::KittyRented);
+
+            Self::lock(&who, amount)?;
+            BoostForKitty::<T>::mutate(kitty_id, |boost| *boost = boost.saturating_add(amount));
+            Self::update_leaderboards(&kitty_id);
+            Self::deposit_event(RawEvent::Boosted(kitty_id, who, amount));
+            Ok(())
+        }
+
+        /// Remove some or all of a kitty's power boost and unlock the associated funds.
+        ///
+        /// Amounts larger than the kitty's boost recoup the entire boost. Funds locked against
+        /// the owner's other kitties are not affected. A kitty's boost cannot change while it is
+        /// under auction or out on rental.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        pub fn recoup(origin, kitty_id: T::Hash, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+            ensure!(!Auctions::<T>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+            ensure!(!Rentals::<T>::contains_key(kitty_id), Error::<T>::KittyRented);
+
+            ensure!(!Self::boost_for_kitty(&kitty_id).is_zero(), Error::<T>::NoBoost);
+
+            let amount = Self::reduce_boost(&who, &kitty_id, amount);
+            Self::update_leaderboards(&kitty_id);
+            Self::deposit_event(RawEvent::Recouped(kitty_id, who, amount));
+            Ok(())
+        }
+
+        /// Transfer a kitty to another account along with the funds locked against it.
+        ///
+        /// The sender's lock shrinks by the kitty's base price and boost and the recipient's lock
+        /// grows by the same amount, so the recipient must be able to cover it.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        pub fn transfer(origin, dest: T::AccountId, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+
+            Self::do_transfer(&who, &dest, &kitty_id)
+        }
+
+        /// Approve an account to transfer a kitty, or clear the approval with `None`.
+        ///
+        /// A kitty has at most one approved account, and the approval is cleared whenever the
+        /// kitty changes hands.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner or an operator for
+        /// its owner.
+        #[weight = 10_000]
+        pub fn approve(origin, kitty_id: T::Hash, spender: Option<T::AccountId>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let owner = T::Kitties::owner_of(&kitty_id);
+            ensure!(owner == who || Self::is_operator(&owner, &who), Error::<T>::NotApproved);
+
+            match spender.clone() {
+                Some(spender) => Approvals::<T>::insert(kitty_id, (owner.clone(), spender)),
+                None => Approvals::<T>::remove(kitty_id),
+            }
+            Self::deposit_event(RawEvent::Approved(kitty_id, owner, spender));
+            Ok(())
+        }
+
+        /// Approve an operator to transfer and approve all of the sender's kitties, or revoke it.
+        ///
+        /// The dispatch origin for this call must be Signed.
+        #[weight = 10_000]
+        pub fn set_approval_for_all(origin, operator: T::AccountId, approved: bool) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+
+            if approved {
+                Operators::<T>::insert(&who, &operator, true);
+            } else {
+                Operators::<T>::remove(&who, &operator);
+            }
+            Self::deposit_event(RawEvent::ApprovedForAll(who, operator, approved));
+            Ok(())
+        }
+
+        /// Transfer a kitty on its owner's behalf along with the funds locked against it.
+        ///
+        /// The recipient's lock grows by the kitty's base price and boost, as with `transfer`.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner, its approved
+        /// spender or an operator for its owner.
+        #[weight = 10_000]
+        pub fn transfer_from(
+            origin,
+            from: T::AccountId,
+            dest: T::AccountId,
+            kitty_id: T::Hash
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == from, Error::<T>::NotKittyOwner);
+            ensure!(Self::can_transfer(&who, &from, &kitty_id), Error::<T>::NotApproved);
+
+            Self::do_transfer(&from, &dest, &kitty_id)
+        }
+
+        /// Post an intent to breed, optionally addressed to a specific kitty or owner.
+        ///
+        /// The kitty must have a power boost. The intent expires after `FlirtPeriod` blocks and
+        /// replaces any intent the kitty already has open.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's user.
+        #[weight = 10_000]
+        pub fn flirt(origin, kitty_id: T::Hash, target: Option<FlirtTargetOf<T>>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::user_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(!Self::boost_for_kitty(&kitty_id).is_zero(), Error::<T>::NoBoost);
+
+            let expires = <frame_system::Module<T>>::block_number().saturating_add(T::FlirtPeriod::get());
+            // A reposted intent replaces the old one's place in the expiry queue.
+            if let Some(intent) = Self::breeding_intent(kitty_id) {
+                Tasks::<T>::remove((Queue::Intents, intent.expires), intent.position);
+            }
+            BreedingIntents::<T>::insert(kitty_id, BreedingIntent{
+                poster: who.clone(),
+                expires: expires,
+                position: Self::schedule(expires, Task::ExpireIntent(kitty_id)),
+                target: target,
+            });
+            Self::deposit_event(RawEvent::Flirted(kitty_id, who, expires));
+            Ok(())
+        }
+
+        /// Cancel a kitty's open intent to breed.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's user.
+        #[weight = 10_000]
+        pub fn cancel_flirt(origin, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::user_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(BreedingIntents::<T>::contains_key(kitty_id), Error::<T>::NoBreedingIntent);
+
+            BreedingIntents::<T>::remove(kitty_id);
+            Self::deposit_event(RawEvent::FlirtCancelled(kitty_id));
+            Ok(())
+        }
+
+        /// Respond to a partner's intent to breed, producing an offspring.
+        ///
+        /// Both kitties must have a power boost. The offspring inherits one allele of every trait
+        /// from each parent, subject to mutation, and each parent that is not out on rental
+        /// contributes a random portion of its boost to the offspring. The offspring is assigned at
+        /// random to one of the parents' users, who must be able to cover the funds it locks.
+        ///
+        /// The dispatch origin for this call must be Signed by the user of `kitty_id`.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn breed(origin, kitty_id: T::Hash, partner_id: T::Hash, name: Vec<u8>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::user_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+
+            let now = <frame_system::Module<T>>::block_number();
+            let intent = Self::breeding_intent(&partner_id).ok_or(Error::<T>::NoBreedingIntent)?;
+            ensure!(intent.expires > now, Error::<T>::NoBreedingIntent);
+            // An intent does not survive its poster giving up the kitty.
+            ensure!(Self::user_of(&partner_id) == intent.poster, Error::<T>::NoBreedingIntent);
+            match intent.target {
+                Some(FlirtTarget::Kitty(target)) => ensure!(target == kitty_id, Error::<T>::NotFlirtTarget),
+                Some(FlirtTarget::Owner(target)) => ensure!(target == who, Error::<T>::NotFlirtTarget),
+                None => (),
+            }
+
+            Self::do_breed(&who, kitty_id, partner_id, name, None)?;
+            BreedingIntents::<T>::remove(partner_id);
+            Ok(())
+        }
+
+        /// Offer a kitty as a sire for a fee, or change the fee of an existing offer.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's user.
+        #[weight = 10_000]
+        pub fn offer_sire(origin, kitty_id: T::Hash, fee: BalanceOf<T>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::user_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+
+            SireListings::<T>::insert(kitty_id, (who.clone(), fee));
+            Self::deposit_event(RawEvent::SireOffered(kitty_id, who, fee));
+            Ok(())
+        }
+
+        /// Withdraw a kitty's offer as a sire.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's user.
+        #[weight = 10_000]
+        pub fn withdraw_sire(origin, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::user_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(SireListings::<T>::contains_key(kitty_id), Error::<T>::NotOfferedAsSire);
+
+            SireListings::<T>::remove(kitty_id);
+            Self::deposit_event(RawEvent::SireWithdrawn(kitty_id));
+            Ok(())
+        }
+
+        /// Pay a sire's fee to its owner and breed it with one of the sender's kitties.
+        ///
+        /// The offspring always goes to the sender and the sire stays with its owner. Breeding
+        /// otherwise follows the same rules as `breed`. The breeding fails if the fee is higher
+        /// than `max_fee`.
+        ///
+        /// The dispatch origin for this call must be Signed by the user of `kitty_id`.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn breed_with_sire(
+            origin,
+            kitty_id: T::Hash,
+            sire_id: T::Hash,
+            max_fee: BalanceOf<T>,
+            name: Vec<u8>,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::user_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            let (sire_owner, fee) = Self::sire_listing(&sire_id).ok_or(Error::<T>::NotOfferedAsSire)?;
+            // An offer does not survive its owner giving up the sire.
+            ensure!(Self::user_of(&sire_id) == sire_owner, Error::<T>::NotOfferedAsSire);
+            ensure!(fee <= max_fee, Error::<T>::PriceTooHigh);
+
+            T::Currency::transfer(&who, &sire_owner, fee, ExistenceRequirement::KeepAlive)?;
+            Self::do_breed(&who, kitty_id, sire_id, name, Some(who.clone()))?;
+            Self::deposit_event(RawEvent::SireHired(sire_id, who, fee));
+            Ok(())
+        }
+
+        /// List a kitty for sale at a fixed price, or change the price of an existing listing.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        pub fn sell(origin, kitty_id: T::Hash, price: BalanceOf<T>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(!Auctions::<T>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+            ensure!(!Rentals::<T>::contains_key(kitty_id), Error::<T>::KittyRented);
+
+            Listings::<T>::insert(kitty_id, Listing{seller: who.clone(), pricing: Pricing::Fixed(price)});
+            Self::deposit_event(RawEvent::Listed(kitty_id, who, price));
+            Ok(())
+        }
+
+        /// List a kitty for sale at a price that declines from `start_price` to `end_price` over
+        /// `duration` blocks, replacing any existing listing.
+        ///
+        /// The first buyer to pay the current price wins the kitty.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        pub fn sell_declining(
+            origin,
+            kitty_id: T::Hash,
+            start_price: BalanceOf<T>,
+            end_price: BalanceOf<T>,
+            duration: T::BlockNumber,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(!Auctions::<T>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+            ensure!(!Rentals::<T>::contains_key(kitty_id), Error::<T>::KittyRented);
+            ensure!(start_price >= end_price, Error::<T>::PriceIncreases);
+            ensure!(!duration.is_zero(), Error::<T>::ZeroDuration);
+
+            Listings::<T>::insert(kitty_id, Listing{
+                seller: who.clone(),
+                pricing: Pricing::Declining{
+                    start_price: start_price,
+                    end_price: end_price,
+                    start: <frame_system::Module<T>>::block_number(),
+                    duration: duration,
+                },
+            });
+            Self::deposit_event(RawEvent::ListedDeclining(kitty_id, who, start_price, end_price, duration));
+            Ok(())
+        }
+
+        /// Cancel a kitty's listing.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        pub fn cancel_sale(origin, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(Listings::<T>::contains_key(kitty_id), Error::<T>::NotListed);
+
+            Listings::<T>::remove(kitty_id);
+            Self::deposit_event(RawEvent::Delisted(kitty_id));
+            Ok(())
+        }
+
+        /// Buy a listed kitty, paying its current price to the seller.
+        ///
+        /// The purchase fails if the price is higher than `max_price`, which guards against the
+        /// seller repricing the kitty after the purchase was submitted. The buyer must also be
+        /// able to cover the funds the kitty locks.
+        ///
+        /// The dispatch origin for this call must be Signed.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn buy(origin, kitty_id: T::Hash, max_price: BalanceOf<T>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let listing = Self::listing(&kitty_id).ok_or(Error::<T>::NotListed)?;
+            // A listing does not survive its seller giving up the kitty.
+            ensure!(T::Kitties::owner_of(&kitty_id) == listing.seller, Error::<T>::NotListed);
+            ensure!(listing.seller != who, Error::<T>::CannotBuyOwnKitty);
+            let price = Self::price_at(&listing.pricing, <frame_system::Module<T>>::block_number());
+            ensure!(price <= max_price, Error::<T>::PriceTooHigh);
+
+            Self::do_sale(&listing.seller, &who, &kitty_id, price, Funds::Free)
+        }
+
+        /// Offer to buy a kitty, whether or not it is listed, replacing any earlier offer by the
+        /// sender.
+        ///
+        /// The amount is reserved in the sender's account until the owner accepts the offer, the
+        /// sender withdraws it, or it expires after `OfferPeriod` blocks. Offers must be at least
+        /// the existential deposit. A kitty holds at most `MaxOffersPerKitty` open offers, and once
+        /// it is full a new offer must beat the lowest one, which is refunded.
+        ///
+        /// The dispatch origin for this call must be Signed.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn make_offer(origin, kitty_id: T::Hash, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(MetadataForKitty::<T>::contains_key(kitty_id), Error::<T>::UnknownKitty);
+            ensure!(T::Kitties::owner_of(&kitty_id) != who, Error::<T>::CannotBuyOwnKitty);
+            ensure!(!amount.is_zero(), Error::<T>::ZeroAmount);
+            ensure!(amount >= T::Currency::minimum_balance(), Error::<T>::OfferTooLow);
+
+            if let Some(offer) = Self::take_offer(&kitty_id, &who) {
+                T::Currency::unreserve(&who, offer.amount);
+            }
+            if Self::offer_count(kitty_id) >= T::MaxOffersPerKitty::get() {
+                let (bidder, lowest) = Offers::<T>::iter_prefix(kitty_id)
+                    .min_by_key(|(_, offer)| offer.amount)
+                    .ok_or(Error::<T>::OfferTooLow)?;
+                ensure!(amount > lowest.amount, Error::<T>::OfferTooLow);
+                Self::take_offer(&kitty_id, &bidder);
+                T::Currency::unreserve(&bidder, lowest.amount);
+                Self::deposit_event(RawEvent::OfferWithdrawn(kitty_id, bidder));
+            }
+            T::Currency::reserve(&who, amount)?;
+
+            let expires = <frame_system::Module<T>>::block_number().saturating_add(T::OfferPeriod::get());
+            Offers::<T>::insert(kitty_id, &who, Offer{amount: amount, expires: expires});
+            OfferCount::<T>::mutate(kitty_id, |count| *count += 1);
+            Self::schedule(expires, Task::ExpireOffer(kitty_id, who.clone()));
+            Self::deposit_event(RawEvent::OfferMade(kitty_id, who, amount, expires));
+            Ok(())
+        }
+
+        /// Withdraw an offer to buy a kitty and unreserve its funds.
+        ///
+        /// The dispatch origin for this call must be Signed by the bidder.
+        #[weight = 10_000]
+        pub fn withdraw_offer(origin, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let offer = Self::take_offer(&kitty_id, &who).ok_or(Error::<T>::NoOffer)?;
+
+            T::Currency::unreserve(&who, offer.amount);
+            Self::deposit_event(RawEvent::OfferWithdrawn(kitty_id, who));
+            Ok(())
+        }
+
+        /// Accept an offer to buy a kitty, selling it to the bidder for the reserved amount.
+        ///
+        /// The sale fails if the offer is lower than `min_amount`, which guards against the
+        /// bidder replacing the offer after the acceptance was submitted. The bidder must also be
+        /// able to cover the funds the kitty locks.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn accept_offer(
+            origin,
+            kitty_id: T::Hash,
+            bidder: T::AccountId,
+            min_amount: BalanceOf<T>,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            let offer = Self::take_offer(&kitty_id, &bidder).ok_or(Error::<T>::NoOffer)?;
+            ensure!(offer.expires > <frame_system::Module<T>>::block_number(), Error::<T>::NoOffer);
+            ensure!(offer.amount >= min_amount, Error::<T>::OfferTooLow);
+
+            Self::do_sale(&who, &bidder, &kitty_id, offer.amount, Funds::Reserved)
+        }
+
+        /// Set the portion of every sale price paid to the treasury.
+        ///
+        /// The fee and `RoyaltyPercent` together may not exceed the whole price.
+        ///
+        /// The dispatch origin for this call must be Root.
+        #[weight = 10_000]
+        pub fn set_market_fee(origin, fee: Perbill) -> dispatch::DispatchResult {
+            ensure_root(origin)?;
+            let royalty = T::RoyaltyPercent::get();
+            ensure!(
+                fee.deconstruct() as u64 + royalty.deconstruct() as u64 <= Perbill::one().deconstruct() as u64,
+                Error::<T>::FeeTooHigh
+            );
+
+            MarketFee::put(fee);
+            Self::deposit_event(RawEvent::MarketFeeSet(fee));
+            Ok(())
+        }
+
+        /// Put a kitty up for auction, ending any fixed-price listing, siring offer and intent to
+        /// breed.
+        ///
+        /// Bids must meet `reserve` and beat the high bid by at least `min_increment`. Bidding
+        /// closes after `duration` blocks, when the kitty is sold to the high bidder. The kitty
+        /// cannot be sold, transferred or bred by other means until then.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        pub fn start_auction(
+            origin,
+            kitty_id: T::Hash,
+            reserve: BalanceOf<T>,
+            min_increment: BalanceOf<T>,
+            duration: T::BlockNumber,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(!Auctions::<T>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+            ensure!(!Rentals::<T>::contains_key(kitty_id), Error::<T>::KittyRented);
+            ensure!(!duration.is_zero(), Error::<T>::ZeroDuration);
+
+            let end = <frame_system::Module<T>>::block_number().saturating_add(duration);
+            Listings::<T>::remove(kitty_id);
+            SireListings::<T>::remove(kitty_id);
+            BreedingIntents::<T>::remove(kitty_id);
+            Auctions::<T>::insert(kitty_id, Auction{
+                seller: who.clone(),
+                reserve: reserve,
+                min_increment: min_increment,
+                end: end,
+                high_bid: None,
+            });
+            Self::schedule(end, Task::SettleAuction(kitty_id));
+            Self::deposit_event(RawEvent::AuctionStarted(kitty_id, who, reserve, end));
+            Ok(())
+        }
 
+        /// Bid on a kitty under auction, reserving the bid in the sender's account.
+        ///
+        /// The previous high bid is unreserved. The sender must be able to lock the kitty's base
+        /// price and boost on top of the bid.
+        ///
+        /// The dispatch origin for this call must be Signed.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn bid(origin, kitty_id: T::Hash, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let mut auction = Self::auction(&kitty_id).ok_or(Error::<T>::NotInAuction)?;
+            ensure!(<frame_system::Module<T>>::block_number() < auction.end, Error::<T>::AuctionEnded);
+            ensure!(auction.seller != who, Error::<T>::CannotBuyOwnKitty);
+            ensure!(amount >= auction.reserve, Error::<T>::BidTooLow);
+            if let Some((bidder, high_bid)) = auction.high_bid.take() {
+                ensure!(amount >= high_bid.saturating_add(auction.min_increment), Error::<T>::BidTooLow);
+                T::Currency::unreserve(&bidder, high_bid);
+            }
+            // The winner must be able to lock the kitty's stake once the bid is paid.
+            let locked = Self::locked_for_account(&who).saturating_add(Self::stake_of(&kitty_id));
+            ensure!(
+                T::Currency::free_balance(&who).saturating_sub(amount) >= locked,
+                Error::<T>::InsufficientBalance
+            );
+
+            T::Currency::reserve(&who, amount)?;
+            auction.high_bid = Some((who.clone(), amount));
+            Auctions::<T>::insert(kitty_id, auction);
+            Self::deposit_event(RawEvent::BidPlaced(kitty_id, who, amount));
             Ok(())
         }
 
-        // TODO: BOOST
-        // power up a kitty by locking more funds
-        // increases power without altering DNA
-        // store as metadata in this pallet
+        /// Cancel an auction that has no bids.
+        ///
+        /// The dispatch origin for this call must be Signed by the seller.
+        #[weight = 10_000]
+        pub fn cancel_auction(origin, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let auction = Self::auction(&kitty_id).ok_or(Error::<T>::NotInAuction)?;
+            ensure!(auction.seller == who, Error::<T>::NotKittyOwner);
+            ensure!(auction.high_bid.is_none(), Error::<T>::AuctionHasBids);
 
-        // TODO: RECOUP
-        // remove boost and associated lock
+            Auctions::<T>::remove(kitty_id);
+            Self::deposit_event(RawEvent::AuctionCancelled(kitty_id));
+            Ok(())
+        }
 
-        // TODO: FLIRT
-        // post intent to breed, must have power boost
+        /// Settle an auction whose bidding has closed.
+        ///
+        /// Auctions are settled automatically when they end, so this is only needed for auctions
+        /// whose settlement was deferred by a busy block.
+        ///
+        /// The dispatch origin for this call must be Signed.
+        #[weight = 10_000]
+        pub fn settle_auction(origin, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            ensure_signed(origin)?;
+            let auction = Self::auction(&kitty_id).ok_or(Error::<T>::NotInAuction)?;
+            ensure!(<frame_system::Module<T>>::block_number() >= auction.end, Error::<T>::AuctionNotEnded);
+
+            Self::settle(&kitty_id);
+            Ok(())
+        }
 
-        // TODO: BREED
-        // respond to intent to breed, must have power boost
-        // DNA and power derived from parents
-        // each parent randomly contributes power from boost
-        // offspring owner randomly assigned between parent owners
+        /// Offer a kitty for rent at `price` for `duration` blocks, replacing any earlier offer.
+        ///
+        /// The kitty's boost stays with the owner during the rental: it cannot change and offspring
+        /// the borrower breeds inherit none of it.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        pub fn offer_rental(
+            origin,
+            kitty_id: T::Hash,
+            price: BalanceOf<T>,
+            duration: T::BlockNumber,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(!Auctions::<T>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+            ensure!(!Rentals::<T>::contains_key(kitty_id), Error::<T>::KittyRented);
+            ensure!(!duration.is_zero(), Error::<T>::ZeroDuration);
 
-        // TODO: SELL
-        // post intent to sell including price
+            RentalOffers::<T>::insert(kitty_id, (who.clone(), price, duration));
+            Self::deposit_event(RawEvent::RentalOffered(kitty_id, who, price, duration));
+            Ok(())
+        }
 
-        // TODO: BUY
-        // respond to intent to sell
-        // transfer funds to seller and transfer kitty ownership
+        /// Withdraw a kitty's offer for rent.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        pub fn withdraw_rental(origin, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(RentalOffers::<T>::contains_key(kitty_id), Error::<T>::NotForRent);
+
+            RentalOffers::<T>::remove(kitty_id);
+            Self::deposit_event(RawEvent::RentalWithdrawn(kitty_id));
+            Ok(())
+        }
+
+        /// Pay a kitty's rental price to its owner and use the kitty until the rental ends.
+        ///
+        /// The sender takes the owner's place for breeding until the kitty returns to its owner
+        /// automatically. Neither party can sell, transfer or release the kitty in the meantime,
+        /// and breeding intents and sire offers do not carry over between them. Offspring bred
+        /// with the kitty inherit none of its boost. The rental fails if the price is higher than
+        /// `max_price`.
+        ///
+        /// The dispatch origin for this call must be Signed.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn rent(origin, kitty_id: T::Hash, max_price: BalanceOf<T>) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let (owner, price, duration) = RentalOffers::<T>::take(kitty_id).ok_or(Error::<T>::NotForRent)?;
+            // An offer does not survive its owner giving up the kitty.
+            ensure!(T::Kitties::owner_of(&kitty_id) == owner, Error::<T>::NotForRent);
+            ensure!(owner != who, Error::<T>::CannotBuyOwnKitty);
+            ensure!(price <= max_price, Error::<T>::PriceTooHigh);
+            ensure!(!Auctions::<T>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+
+            T::Currency::transfer(&who, &owner, price, ExistenceRequirement::KeepAlive)?;
+            let end = <frame_system::Module<T>>::block_number().saturating_add(duration);
+            Listings::<T>::remove(kitty_id);
+            SireListings::<T>::remove(kitty_id);
+            BreedingIntents::<T>::remove(kitty_id);
+            Rentals::<T>::insert(kitty_id, Rental{owner: owner.clone(), user: who.clone(), end: end});
+            Self::schedule(end, Task::EndRental(kitty_id));
+            Self::deposit_event(RawEvent::Rented(kitty_id, owner, who, end));
+            Ok(())
+        }
+
+        /// Challenge another kitty to battle for `stake`, replacing the kitty's open challenge.
+        ///
+        /// The stake is reserved in the sender's account until the battle is fought or the sender
+        /// cancels the challenge. An accepted challenge cannot be replaced.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's user.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn challenge(
+            origin,
+            kitty_id: T::Hash,
+            opponent_id: T::Hash,
+            stake: BalanceOf<T>,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::user_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(MetadataForKitty::<T>::contains_key(opponent_id), Error::<T>::UnknownKitty);
+            ensure!(Self::user_of(&opponent_id) != who, Error::<T>::CannotBattleSelf);
+
+            if let Some(challenge) = Challenges::<T>::take(kitty_id) {
+                ensure!(challenge.accepted_by.is_none(), Error::<T>::ChallengeAccepted);
+                T::Currency::unreserve(&challenge.challenger, challenge.stake);
+            }
+            T::Currency::reserve(&who, stake)?;
+
+            Challenges::<T>::insert(kitty_id, Challenge{
+                challenger: who.clone(),
+                opponent: opponent_id,
+                stake: stake,
+                accepted_by: None,
+            });
+            Self::deposit_event(RawEvent::Challenged(kitty_id, opponent_id, who, stake));
+            Ok(())
+        }
+
+        /// Cancel a kitty's open challenge and return its stake. An accepted challenge cannot be
+        /// cancelled.
+        ///
+        /// The dispatch origin for this call must be Signed by the challenger.
+        #[weight = 10_000]
+        pub fn cancel_challenge(origin, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let challenge = Self::challenge(kitty_id).ok_or(Error::<T>::NoChallenge)?;
+            ensure!(challenge.challenger == who, Error::<T>::NoChallenge);
+            ensure!(challenge.accepted_by.is_none(), Error::<T>::ChallengeAccepted);
+
+            Challenges::<T>::remove(kitty_id);
+            T::Currency::unreserve(&who, challenge.stake);
+            Self::deposit_event(RawEvent::ChallengeCancelled(kitty_id));
+            Ok(())
+        }
+
+        /// Accept a kitty's challenge, matching its stake, to battle it at the start of the next
+        /// block.
+        ///
+        /// Each kitty wins with probability proportional to its share of the two kitties' power.
+        /// The winner's user takes both stakes. The battle is fought in a later block than the one
+        /// that includes this call, so its outcome is not known when the call is submitted.
+        ///
+        /// The dispatch origin for this call must be Signed by the user of the challenged kitty.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn accept(origin, challenger_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let mut challenge = Self::challenge(challenger_id).ok_or(Error::<T>::NoChallenge)?;
+            ensure!(challenge.accepted_by.is_none(), Error::<T>::ChallengeAccepted);
+            let opponent_id = challenge.opponent;
+            ensure!(Self::user_of(&opponent_id) == who, Error::<T>::NotKittyOwner);
+            // A challenge does not survive its challenger giving up the kitty.
+            ensure!(Self::user_of(&challenger_id) == challenge.challenger, Error::<T>::NoChallenge);
+
+            T::Currency::reserve(&who, challenge.stake)?;
+            challenge.accepted_by = Some(who.clone());
+            Challenges::<T>::insert(challenger_id, challenge);
+            let next = <frame_system::Module<T>>::block_number() + One::one();
+            Self::schedule(next, Task::ResolveBattle(challenger_id));
+            Self::deposit_event(RawEvent::ChallengeAccepted(challenger_id, opponent_id, who));
+            Ok(())
+        }
+
+        /// Create a knockout tournament for up to `max_entrants` kitties, each entered for
+        /// `entry_fee`.
+        ///
+        /// At `start` the bracket is seeded at random and a round is played every block until a
+        /// champion remains. The entry fees are held in the tournament's account and paid out to
+        /// the top finishers as set by `TournamentPrizes`. At most `MaxRunningTournaments`
+        /// tournaments may be open at once.
+        ///
+        /// The dispatch origin for this call must be Signed.
+        #[weight = 10_000]
+        pub fn create_tournament(
+            origin,
+            max_entrants: u32,
+            entry_fee: BalanceOf<T>,
+            start: T::BlockNumber,
+        ) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(
+                max_entrants >= 2 && max_entrants <= T::MaxTournamentSize::get(),
+                Error::<T>::BadTournamentSize
+            );
+            ensure!(entry_fee >= T::Currency::minimum_balance(), Error::<T>::EntryFeeTooLow);
+            ensure!(start > <frame_system::Module<T>>::block_number(), Error::<T>::StartInPast);
+            ensure!(Self::open_tournaments() < T::MaxRunningTournaments::get(), Error::<T>::TooManyTournaments);
+
+            OpenTournaments::mutate(|open| *open += 1);
+            let id = NextTournamentId::mutate(|next| {
+                let id = *next;
+                *next = next.wrapping_add(1);
+                id
+            });
+            Tournaments::<T>::insert(id, Tournament{
+                organizer: who.clone(),
+                max_entrants: max_entrants,
+                entry_fee: entry_fee,
+                start: start,
+            });
+            Self::schedule(start, Task::StartTournament(id));
+            Self::deposit_event(RawEvent::TournamentCreated(id, who, max_entrants, entry_fee, start));
+            Ok(())
+        }
+
+        /// Enter a kitty into a tournament, paying its entry fee into the prize pool.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's user.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn enter_tournament(origin, tournament_id: u32, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(Self::user_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            let tournament = Self::tournament(tournament_id).ok_or(Error::<T>::UnknownTournament)?;
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(tournament.start > now, Error::<T>::TournamentStarted);
+
+            Brackets::<T>::try_mutate(tournament_id, |bracket| -> dispatch::DispatchResult {
+                ensure!((bracket.round.len() as u32) < tournament.max_entrants, Error::<T>::TournamentFull);
+                ensure!(bracket.round.iter().all(|(id, _)| *id != kitty_id), Error::<T>::AlreadyEntered);
+                bracket.round.push((kitty_id, who.clone()));
+                Ok(())
+            })?;
+            let pool = Self::tournament_account(tournament_id);
+            T::Currency::transfer(&who, &pool, tournament.entry_fee, ExistenceRequirement::KeepAlive)?;
+            Self::deposit_event(RawEvent::TournamentEntered(tournament_id, kitty_id, who));
+            Ok(())
+        }
+
+        /// Call off a tournament that has not started and refund its entry fees.
+        ///
+        /// The dispatch origin for this call must be Signed by the tournament's organizer.
+        #[weight = 10_000]
+        pub fn cancel_tournament(origin, tournament_id: u32) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            let tournament = Self::tournament(tournament_id).ok_or(Error::<T>::UnknownTournament)?;
+            ensure!(tournament.organizer == who, Error::<T>::NotOrganizer);
+            let now = <frame_system::Module<T>>::block_number();
+            ensure!(tournament.start > now, Error::<T>::TournamentStarted);
+
+            Self::call_off(tournament_id, &tournament);
+            Ok(())
+        }
+
+        /// Burn a kitty and unlock the funds locked against it.
+        ///
+        /// The dispatch origin for this call must be Signed by the kitty's owner.
+        #[weight = 10_000]
+        #[transactional]
+        pub fn release(origin, kitty_id: T::Hash) -> dispatch::DispatchResult {
+            let who = ensure_signed(origin)?;
+            ensure!(T::Kitties::owner_of(&kitty_id) == who, Error::<T>::NotKittyOwner);
+            ensure!(!Auctions::<T>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+            ensure!(!Rentals::<T>::contains_key(kitty_id), Error::<T>::KittyRented);
+
+            let stake = Self::stake_of(&kitty_id);
+            T::Kitties::burn(&kitty_id)?;
+            Self::unlock(&who, stake);
+            Self::remove_kitty_records(&kitty_id);
+
+            Self::update_leaderboards(&kitty_id);
+            Self::deposit_event(RawEvent::Released(kitty_id, who));
+            Ok(())
+        }
+    }
+}
+
+impl<T: Trait> Module<T> {
+    /// The account of the treasury that collects marketplace fees.
+    pub fn treasury_account() -> T::AccountId {
+        T::TreasuryId::get().into_account()
+    }
+
+    /// The account that uses a kitty for breeding: its borrower while it is out on rental, and
+    /// otherwise its owner.
+    pub fn user_of(kitty_id: &T::Hash) -> T::AccountId {
+        let owner = T::Kitties::owner_of(kitty_id);
+        match Self::rental(kitty_id) {
+            Some(rental) if rental.owner == owner => rental.user,
+            _ => owner,
+        }
+    }
+
+    /// Whether `who` may transfer a kitty owned by `owner`.
+    ///
+    /// Approvals granted by a previous owner are ignored, since `pallet_commodities` can move a
+    /// kitty without clearing them.
+    pub fn can_transfer(who: &T::AccountId, owner: &T::AccountId, kitty_id: &T::Hash) -> bool {
+        who == owner
+            || Self::is_operator(owner, who)
+            || Self::approval(kitty_id)
+                .map_or(false, |(approver, spender)| &approver == owner && &spender == who)
+    }
+
+    /// Walk a kitty's pedigree back up to `depth` generations, capped at `MaxPedigreeDepth`.
+    ///
+    /// Returns each distinct ancestor along with the number of generations it is removed from
+    /// the kitty, nearest ancestors first.
+    pub fn ancestors(kitty_id: &T::Hash, depth: u32) -> Vec<(T::Hash, u32)> {
+        let depth = depth.min(T::MaxPedigreeDepth::get());
+        let mut ancestors: Vec<(T::Hash, u32)> = Vec::new();
+        let mut generation = sp_std::vec![*kitty_id];
+        for distance in 1..=depth {
+            let mut parents = Vec::new();
+            for kitty_id in &generation {
+                if let Some((kitty, partner)) = Self::lineage_for_kitty(kitty_id).parents {
+                    for parent in [kitty, partner].iter() {
+                        if !ancestors.iter().any(|(ancestor, _)| ancestor == parent) {
+                            ancestors.push((*parent, distance));
+                            parents.push(*parent);
+                        }
+                    }
+                }
+            }
+
+            if parents.is_empty() {
+                break;
+            }
+            generation = parents;
+        }
+
+        ancestors
+    }
+
+    /// The kitties bred from a kitty, in no particular order.
+    pub fn children_of(kitty_id: &T::Hash) -> Vec<T::Hash> {
+        Children::<T>::iter_prefix(kitty_id).map(|(child_id, _)| child_id).collect()
+    }
+
+    /// Whether one kitty descends from the other, or the two share an ancestor within
+    /// `MaxInbreedingDepth` generations.
+    fn related(kitty_id: &T::Hash, other_id: &T::Hash) -> bool {
+        // Always look back far enough to catch siblings.
+        let depth = T::MaxInbreedingDepth::get().max(1);
+        let ancestors = Self::ancestors(kitty_id, depth);
+        let other_ancestors = Self::ancestors(other_id, depth);
+
+        ancestors.iter().any(|(ancestor, _)| ancestor == other_id)
+            || other_ancestors.iter().any(|(ancestor, _)| ancestor == kitty_id)
+            || ancestors
+                .iter()
+                .any(|(ancestor, _)| other_ancestors.iter().any(|(other, _)| other == ancestor))
+    }
+
+    /// The price a listed kitty can be bought for in the current block.
+    pub fn current_price(kitty_id: &T::Hash) -> Option<BalanceOf<T>> {
+        Self::listing(kitty_id)
+            .map(|listing| Self::price_at(&listing.pricing, <frame_system::Module<T>>::block_number()))
+    }
+
+    /// The price set by a listing's pricing at a block.
+    fn price_at(pricing: &Pricing<BalanceOf<T>, T::BlockNumber>, now: T::BlockNumber) -> BalanceOf<T> {
+        match *pricing {
+            Pricing::Fixed(price) => price,
+            Pricing::Declining{start_price, end_price, start, duration} => {
+                let elapsed = now.saturating_sub(start);
+                if elapsed >= duration {
+                    end_price
+                } else {
+                    let decline = Perbill::from_rational_approximation(elapsed, duration);
+                    start_price.saturating_sub(decline * start_price.saturating_sub(end_price))
+                }
+            }
+        }
+    }
+
+    /// Decode the traits in a kitty's DNA.
+    pub fn genome_of(kitty_id: &T::Hash) -> Option<KittyGenome> {
+        Self::kitty_info(kitty_id).map(|info| KittyGenome::from_dna(info.dna.as_ref()))
+    }
+
+    /// A kitty's power: the power in its DNA plus one for every base price of boost.
+    pub fn power_of(kitty_id: &T::Hash) -> Option<u64> {
+        let boost = Self::boost_for_kitty(kitty_id) / T::BasePrice::get().max(One::one());
+        Self::genome_of(kitty_id)
+            .map(|genome| (genome.power as u64).saturating_add(boost.saturated_into::<u64>()))
+    }
+
+    /// How rare a kitty's traits are. See [`genome::rarity`].
+    pub fn rarity_of(kitty_id: &T::Hash) -> Option<u32> {
+        Self::kitty_info(kitty_id).map(|info| genome::rarity(info.dna.as_ref(), &T::Mutations::get()))
+    }
+
+    /// The most powerful kitties and their power, most powerful first.
+    pub fn power_leaders() -> Vec<(T::Hash, u64)> {
+        Self::leaders(PowerLeaders::<T>::get())
+    }
+
+    /// The rarest kitties and their rarity score, rarest first.
+    pub fn rarity_leaders() -> Vec<(T::Hash, u32)> {
+        Self::leaders(RarityLeaders::<T>::get())
+    }
+
+    /// The oldest kitties and their date of birth, oldest first.
+    pub fn age_leaders() -> Vec<(T::Hash, MomentOf<T>)> {
+        Self::leaders(AgeLeaders::<T>::get())
+    }
+
+    /// The leading `LeaderboardSize` kitties among a leaderboard's candidates.
+    fn leaders<S>(mut candidates: Vec<(T::Hash, S)>) -> Vec<(T::Hash, S)> {
+        candidates.truncate(T::LeaderboardSize::get() as usize);
+        candidates
+    }
+
+    /// Move a kitty to its current place on every leaderboard, or off them if it no longer exists.
+    fn update_leaderboards(kitty_id: &T::Hash) {
+        let info = Self::kitty_info(kitty_id);
+        let power = Self::power_of(kitty_id);
+        let rarity = info.as_ref().map(|info| genome::rarity(info.dna.as_ref(), &T::Mutations::get()));
+        let dob = info.map(|info| info.dob);
+
+        PowerLeaders::<T>::mutate(|board| Self::rank(board, kitty_id, power, |a, b| a > b));
+        RarityLeaders::<T>::mutate(|board| Self::rank(board, kitty_id, rarity, |a, b| a > b));
+        AgeLeaders::<T>::mutate(|board| Self::rank(board, kitty_id, dob, |a, b| a < b));
+    }
+
+    /// Move a kitty to its place among a leaderboard's candidates sorted best first, or off them if
+    /// `score` is `None`, keeping at most twice `LeaderboardSize` candidates.
+    ///
+    /// The candidates past the leaders take the place of leaders that drop out or fall behind. A
+    /// kitty that is not a candidate only joins them when its own score next changes.
+    fn rank<S>(
+        board: &mut Vec<(T::Hash, S)>,
+        kitty_id: &T::Hash,
+        score: Option<S>,
+        better: impl Fn(&S, &S) -> bool,
+    ) {
+        board.retain(|(id, _)| id != kitty_id);
+        if let Some(score) = score {
+            let place = board.iter().position(|(_, other)| better(&score, other)).unwrap_or(board.len());
+            board.insert(place, (*kitty_id, score));
+            board.truncate(T::LeaderboardSize::get().saturating_mul(2) as usize);
+        }
+    }
+
+    /// The funds a kitty keeps locked in its owner's account: the base price plus any boost.
+    fn stake_of(kitty_id: &T::Hash) -> BalanceOf<T> {
+        T::BasePrice::get().saturating_add(Self::boost_for_kitty(kitty_id))
+    }
+
+    /// Draw a random seed for a subject, mixing in a nonce so that repeated draws in the same
+    /// block differ.
+    fn random_seed(subject: impl Encode) -> T::Hash {
+        let nonce = Nonce::mutate(|nonce| {
+            *nonce = nonce.wrapping_add(1);
+            *nonce
+        });
+        T::Randomness::random(&(MODULE_ID, subject, nonce).encode())
+    }
+
+    /// Look up the attributes that uniquely identify a kitty.
+    fn kitty_info(kitty_id: &T::Hash) -> Option<KittyInfoOf<T>> {
+        T::Kitties::assets_for_account(&T::Kitties::owner_of(kitty_id))
+            .into_iter()
+            .find(|(id, _)| id == kitty_id)
+            .map(|(_, info)| info)
+    }
+
+    /// Take up to `amount` from a kitty's boost and unlock it from the owner's account,
+    /// returning the amount actually taken.
+    fn reduce_boost(owner: &T::AccountId, kitty_id: &T::Hash, amount: BalanceOf<T>) -> BalanceOf<T> {
+        let boost = Self::boost_for_kitty(kitty_id);
+        let amount = amount.min(boost);
+        Self::unlock(owner, amount);
+        if amount == boost {
+            BoostForKitty::<T>::remove(kitty_id);
+        } else {
+            BoostForKitty::<T>::insert(kitty_id, boost - amount);
+        }
+
+        amount
+    }
+
+    /// Breed two kitties, assigning the offspring to `owner` or, if `None`, at random to one of
+    /// the parents' owners.
+    fn do_breed(
+        who: &T::AccountId,
+        kitty_id: T::Hash,
+        partner_id: T::Hash,
+        name: Vec<u8>,
+        owner: Option<T::AccountId>,
+    ) -> dispatch::DispatchResult {
+        ensure!(kitty_id != partner_id, Error::<T>::CannotBreedWithSelf);
+        // Breeding takes boost that bidders are paying for.
+        ensure!(
+            !Auctions::<T>::contains_key(kitty_id) && !Auctions::<T>::contains_key(partner_id),
+            Error::<T>::KittyInAuction
+        );
+        ensure!(!Self::related(&kitty_id, &partner_id), Error::<T>::KittiesRelated);
+
+        let kitty_boost = Self::boost_for_kitty(&kitty_id);
+        let partner_boost = Self::boost_for_kitty(&partner_id);
+        ensure!(!kitty_boost.is_zero() && !partner_boost.is_zero(), Error::<T>::NoBoost);
+        let kitty = Self::kitty_info(&kitty_id).ok_or(Error::<T>::UnknownKitty)?;
+        let partner = Self::kitty_info(&partner_id).ok_or(Error::<T>::UnknownKitty)?;
+        let now = <frame_system::Module<T>>::block_number();
+        Self::ensure_ready(&kitty_id, &kitty, now)?;
+        Self::ensure_ready(&partner_id, &partner, now)?;
+        let kitty_owner = T::Kitties::owner_of(&kitty_id);
+        let partner_owner = T::Kitties::owner_of(&partner_id);
+        let partner_user = Self::user_of(&partner_id);
+
+        let seed = Self::random_seed((who, kitty_id, partner_id));
+        let draw = T::Hashing::hash_of(&seed);
+        let draw = draw.as_ref();
+        let owner = owner.unwrap_or_else(|| {
+            if draw[0] % 2 == 0 {
+                who.clone()
+            } else {
+                partner_user.clone()
+            }
+        });
+        // A rented kitty's boost belongs to its owner, so it passes none of it on.
+        let share = |kitty_id: &T::Hash, byte: u8, boost: BalanceOf<T>| {
+            if Rentals::<T>::contains_key(kitty_id) {
+                Zero::zero()
+            } else {
+                Perbill::from_rational_approximation(byte as u32, u8::max_value() as u32) * boost
+            }
+        };
+
+        let kitty_share = share(&kitty_id, draw[1], kitty_boost);
+        let partner_share = share(&partner_id, draw[2], partner_boost);
+        let boost = Self::reduce_boost(&kitty_owner, &kitty_id, kitty_share)
+            .saturating_add(Self::reduce_boost(&partner_owner, &partner_id, partner_share));
+        Self::lock(&owner, T::BasePrice::get().saturating_add(boost))?;
+
+        let dna = Self::cross_dna(&kitty.dna, &partner.dna, &seed);
+        let child_id = T::Kitties::mint(&owner, KittyInfo{dob: T::Time::now(), dna: dna})?;
+        MetadataForKitty::<T>::insert(child_id, KittyMetadata{name: name});
+        CreatorOf::<T>::insert(child_id, who);
+        if !boost.is_zero() {
+            BoostForKitty::<T>::insert(child_id, boost);
+        }
+
+        let kitty_generation = Self::lineage_for_kitty(&kitty_id).generation;
+        let partner_generation = Self::lineage_for_kitty(&partner_id).generation;
+        ReadyAt::<T>::insert(kitty_id, now.saturating_add(Self::cooldown(kitty_generation)));
+        ReadyAt::<T>::insert(partner_id, now.saturating_add(Self::cooldown(partner_generation)));
+
+        let generation = kitty_generation.max(partner_generation).saturating_add(1);
+        LineageForKitty::<T>::insert(child_id, Lineage{
+            parents: Some((kitty_id, partner_id)),
+            generation: generation,
+        });
+        Children::<T>::insert(kitty_id, child_id, ());
+        Children::<T>::insert(partner_id, child_id, ());
+
+        for kitty_id in [kitty_id, partner_id, child_id].iter() {
+            Self::update_leaderboards(kitty_id);
+        }
+        Self::deposit_event(RawEvent::Bred(child_id, kitty_id, partner_id, owner));
+        Ok(())
+    }
+
+    /// Ensure a kitty is old enough to breed and is not cooling down from breeding.
+    fn ensure_ready(
+        kitty_id: &T::Hash,
+        info: &KittyInfoOf<T>,
+        now: T::BlockNumber,
+    ) -> dispatch::DispatchResult {
+        let age = T::Time::now().saturating_sub(info.dob);
+        ensure!(age >= T::MinBreedingAge::get(), Error::<T>::KittyTooYoung);
+        ensure!(Self::ready_at(kitty_id) <= now, Error::<T>::KittyCoolingDown);
+        Ok(())
+    }
+
+    /// The number of blocks a kitty of a generation rests after breeding.
+    fn cooldown(generation: u32) -> T::BlockNumber {
+        let schedule = T::CooldownSchedule::get();
+        schedule
+            .get(generation as usize)
+            .or_else(|| schedule.last())
+            .copied()
+            .unwrap_or_else(Zero::zero)
+    }
+
+    /// Cross two parents' DNA as the seed dictates, applying the configured mutations.
+    fn cross_dna(dna: &T::Hash, other: &T::Hash, seed: &T::Hash) -> T::Hash {
+        let mut child = T::Hash::default();
+        genome::cross(dna.as_ref(), other.as_ref(), seed.as_ref(), &T::Mutations::get(), child.as_mut());
+        child
+    }
+
+    /// Remove everything this pallet stores about a kitty that no longer exists.
+    fn remove_kitty_records(kitty_id: &T::Hash) {
+        MetadataForKitty::<T>::remove(kitty_id);
+        BoostForKitty::<T>::remove(kitty_id);
+        ReadyAt::<T>::remove(kitty_id);
+        Listings::<T>::remove(kitty_id);
+        SireListings::<T>::remove(kitty_id);
+        BreedingIntents::<T>::remove(kitty_id);
+        CreatorOf::<T>::remove(kitty_id);
+        Approvals::<T>::remove(kitty_id);
+        RentalOffers::<T>::remove(kitty_id);
+        Rentals::<T>::remove(kitty_id);
+        BattleRecords::<T>::remove(kitty_id);
+        if let Some(challenge) = Challenges::<T>::take(kitty_id) {
+            T::Currency::unreserve(&challenge.challenger, challenge.stake);
+            if let Some(opponent) = challenge.accepted_by {
+                T::Currency::unreserve(&opponent, challenge.stake);
+            }
+        }
+        OfferCount::<T>::remove(kitty_id);
+        for (bidder, offer) in Offers::<T>::drain_prefix(kitty_id) {
+            T::Currency::unreserve(&bidder, offer.amount);
+        }
+    }
+
+    /// Move a kitty and its stake from one account to another.
+    #[transactional]
+    fn do_transfer(from: &T::AccountId, to: &T::AccountId, kitty_id: &T::Hash) -> dispatch::DispatchResult {
+        ensure!(!Auctions::<T>::contains_key(kitty_id), Error::<T>::KittyInAuction);
+        ensure!(!Rentals::<T>::contains_key(kitty_id), Error::<T>::KittyRented);
+        let stake = Self::stake_of(kitty_id);
+        Self::lock(to, stake)?;
+        T::Kitties::transfer(to, kitty_id)?;
+        Self::unlock(from, stake);
+        Listings::<T>::remove(kitty_id);
+        SireListings::<T>::remove(kitty_id);
+        BreedingIntents::<T>::remove(kitty_id);
+        RentalOffers::<T>::remove(kitty_id);
+        Approvals::<T>::remove(kitty_id);
+
+        Self::deposit_event(RawEvent::Transferred(*kitty_id, from.clone(), to.clone()));
+        Ok(())
+    }
+
+    /// Lock additional funds in an account, failing if its free balance cannot cover the new total.
+    ///
+    /// Locked funds can only be spent on transaction fees.
+    fn lock(who: &T::AccountId, amount: BalanceOf<T>) -> dispatch::DispatchResult {
+        let locked = Self::locked_for_account(who).saturating_add(amount);
+        ensure!(T::Currency::free_balance(who) >= locked, Error::<T>::InsufficientBalance);
+
+        T::Currency::set_lock(MODULE_ID, who, locked, WithdrawReasons::except(WithdrawReason::TransactionPayment));
+        LockedForAccount::<T>::insert(who, locked);
+        Ok(())
+    }
+
+    /// Unlock funds from an account, removing the lock once nothing remains locked.
+    fn unlock(who: &T::AccountId, amount: BalanceOf<T>) {
+        let locked = Self::locked_for_account(who).saturating_sub(amount);
+        if locked.is_zero() {
+            T::Currency::remove_lock(MODULE_ID, who);
+            LockedForAccount::<T>::remove(who);
+        } else {
+            T::Currency::set_lock(MODULE_ID, who, locked, WithdrawReasons::except(WithdrawReason::TransactionPayment));
+            LockedForAccount::<T>::insert(who, locked);
+        }
+    }
+
+    /// Remove a bidder's offer on a kitty, keeping count of the kitty's open offers.
+    fn take_offer(
+        kitty_id: &T::Hash,
+        bidder: &T::AccountId,
+    ) -> Option<Offer<BalanceOf<T>, T::BlockNumber>> {
+        let offer = Offers::<T>::take(kitty_id, bidder)?;
+        OfferCount::<T>::mutate(kitty_id, |count| *count = count.saturating_sub(1));
+        Some(offer)
+    }
+
+    /// Queue a task to run at block `at`, returning its position in the block's queue.
+    fn schedule(at: T::BlockNumber, task: TaskOf<T>) -> u32 {
+        let key = (task.queue(), at);
+        let position = TaskBounds::<T>::mutate(key, |(_, next)| {
+            let position = *next;
+            *next = next.saturating_add(1);
+            position
+        });
+        Tasks::<T>::insert(key, position, task);
+        position
+    }
+
+    /// Run the tasks in a queue that are due by `now`, returning the number of storage entries
+    /// read.
+    ///
+    /// At most `max` tasks run and at most `max` blocks' queues are read. Tasks that do not fit
+    /// stay queued, and the next call resumes from the earliest block with tasks left.
+    fn service(queue: Queue, now: T::BlockNumber, max: u32) -> u32 {
+        let mut when = IncompleteSince::<T>::take(queue).unwrap_or(now);
+        let (mut ran, mut blocks) = (0, 0);
+
+        while when <= now {
+            if ran >= max || blocks >= max {
+                IncompleteSince::<T>::insert(queue, when);
+                break;
+            }
+            blocks += 1;
+
+            let key = (queue, when);
+            let (first, next) = TaskBounds::<T>::get(key);
+            let last = next.min(first.saturating_add(max - ran));
+            for position in first..last {
+                if let Some(task) = Tasks::<T>::take(key, position) {
+                    Self::run(task, now);
+                }
+            }
+            ran += last - first;
+
+            if last < next {
+                TaskBounds::<T>::insert(key, (last, next));
+                IncompleteSince::<T>::insert(queue, when);
+                break;
+            }
+            TaskBounds::<T>::remove(key);
+            when += One::one();
+        }
+
+        ran + blocks
+    }
+
+    /// Run a task that has fallen due. Tasks whose subject has since been rescheduled or removed
+    /// do nothing.
+    fn run(task: TaskOf<T>, now: T::BlockNumber) {
+        match task {
+            Task::ExpireIntent(kitty_id) => {
+                if Self::breeding_intent(kitty_id).map_or(false, |intent| intent.expires <= now) {
+                    BreedingIntents::<T>::remove(kitty_id);
+                }
+            },
+            Task::ExpireOffer(kitty_id, bidder) => {
+                // A bidder that offered again since this task was queued has a later expiry.
+                if Self::offer(kitty_id, &bidder).map_or(false, |offer| offer.expires <= now) {
+                    if let Some(offer) = Self::take_offer(&kitty_id, &bidder) {
+                        T::Currency::unreserve(&bidder, offer.amount);
+                        Self::deposit_event(RawEvent::OfferWithdrawn(kitty_id, bidder));
+                    }
+                }
+            },
+            Task::SettleAuction(kitty_id) => {
+                // A kitty put up for auction again since this task was queued has a later end.
+                if Self::auction(kitty_id).map_or(false, |auction| auction.end <= now) {
+                    Self::settle(&kitty_id);
+                }
+            },
+            Task::EndRental(kitty_id) => {
+                // A kitty rented again since this task was queued has a later end.
+                if Self::rental(kitty_id).map_or(false, |rental| rental.end <= now) {
+                    if let Some(rental) = Rentals::<T>::take(kitty_id) {
+                        SireListings::<T>::remove(kitty_id);
+                        BreedingIntents::<T>::remove(kitty_id);
+                        Self::deposit_event(RawEvent::Returned(kitty_id, rental.owner, rental.user));
+                    }
+                }
+            },
+            Task::ResolveBattle(kitty_id) => Self::resolve_battle(&kitty_id),
+            Task::StartTournament(tournament_id) => Self::start_tournament(tournament_id),
+        }
+    }
+
+    /// Pay a seller for a kitty and move the kitty to the buyer.
+    ///
+    /// The `MarketFee` portion of the price goes to the treasury and `RoyaltyPercent` of it goes to
+    /// the kitty's creator. A royalty the creator cannot receive, for instance because their
+    /// account no longer exists, goes to the seller.
+    #[transactional]
+    fn do_sale(
+        seller: &T::AccountId,
+        buyer: &T::AccountId,
+        kitty_id: &T::Hash,
+        price: BalanceOf<T>,
+        funds: Funds,
+    ) -> dispatch::DispatchResult {
+        let fee = Self::market_fee() * price;
+        if !fee.is_zero() {
+            Self::pay(buyer, &Self::treasury_account(), fee, &funds)?;
+        }
+
+        let mut proceeds = price.saturating_sub(fee);
+        if let Some(creator) = Self::creator_of(kitty_id) {
+            let royalty = T::RoyaltyPercent::get() * price;
+            if !royalty.is_zero() && Self::pay(buyer, &creator, royalty, &funds).is_ok() {
+                proceeds = proceeds.saturating_sub(royalty);
+                Self::deposit_event(RawEvent::RoyaltyPaid(*kitty_id, creator, royalty));
+            }
+        }
+
+        Self::pay(buyer, seller, proceeds, &funds)?;
+        Self::do_transfer(seller, buyer, kitty_id)?;
+
+        Self::deposit_event(RawEvent::Sold(*kitty_id, seller.clone(), buyer.clone(), price, fee));
+        Ok(())
+    }
+
+    /// Move funds from a buyer to a recipient.
+    fn pay(
+        buyer: &T::AccountId,
+        recipient: &T::AccountId,
+        amount: BalanceOf<T>,
+        funds: &Funds,
+    ) -> dispatch::DispatchResult {
+        match funds {
+            Funds::Free => {
+                T::Currency::transfer(buyer, recipient, amount, ExistenceRequirement::KeepAlive)
+            }
+            Funds::Reserved => {
+                let missing =
+                    T::Currency::repatriate_reserved(buyer, recipient, amount, BalanceStatus::Free)?;
+                ensure!(missing.is_zero(), Error::<T>::InsufficientBalance);
+                Ok(())
+            }
+        }
+    }
+
+    /// Fight a kitty's accepted challenge and pay both stakes to the winner's user.
+    ///
+    /// The stakes are returned instead if the opponent has since been released or the winner
+    /// cannot be paid.
+    fn resolve_battle(kitty_id: &T::Hash) {
+        let challenge = match Challenges::<T>::take(kitty_id) {
+            Some(challenge) => challenge,
+            None => return,
+        };
+        let opponent = match challenge.accepted_by {
+            Some(opponent) => opponent,
+            None => return,
+        };
+        let opponent_id = challenge.opponent;
+        let powers = (Self::power_of(kitty_id), Self::power_of(&opponent_id));
+        let (power, opponent_power) = match powers {
+            (Some(power), Some(opponent_power)) => (power, opponent_power),
+            _ => {
+                T::Currency::unreserve(&challenge.challenger, challenge.stake);
+                T::Currency::unreserve(&opponent, challenge.stake);
+                Self::deposit_event(RawEvent::ChallengeCancelled(*kitty_id));
+                return;
+            },
+        };
+
+        let seed = Self::random_seed((kitty_id, opponent_id));
+        let (winner, loser, winner_id, loser_id) = if battle::wins(power, opponent_power, seed.as_ref()) {
+            (challenge.challenger, opponent, *kitty_id, opponent_id)
+        } else {
+            (opponent, challenge.challenger, opponent_id, *kitty_id)
+        };
+
+        T::Currency::unreserve(&winner, challenge.stake);
+        if T::Currency::repatriate_reserved(&loser, &winner, challenge.stake, BalanceStatus::Free).is_err() {
+            T::Currency::unreserve(&loser, challenge.stake);
+            Self::deposit_event(RawEvent::ChallengeCancelled(*kitty_id));
+            return;
+        }
+        Self::record_battle(&winner_id, &loser_id);
+        Self::deposit_event(RawEvent::Battled(winner_id, loser_id, winner, challenge.stake));
+    }
+
+    /// Count a battle in the winner's and loser's battle records.
+    fn record_battle(winner_id: &T::Hash, loser_id: &T::Hash) {
+        BattleRecords::<T>::mutate(winner_id, |record| record.wins = record.wins.saturating_add(1));
+        BattleRecords::<T>::mutate(loser_id, |record| record.losses = record.losses.saturating_add(1));
+    }
+
+    /// The account that holds a tournament's prize pool.
+    pub fn tournament_account(tournament_id: u32) -> T::AccountId {
+        T::TournamentId::get().into_sub_account(tournament_id)
+    }
+
+    /// Refund a tournament's entry fees and remove it.
+    fn call_off(tournament_id: u32, tournament: &TournamentOf<T>) {
+        let pool = Self::tournament_account(tournament_id);
+        for (kitty_id, entrant) in Brackets::<T>::take(tournament_id).round {
+            let refunded =
+                T::Currency::transfer(&pool, &entrant, tournament.entry_fee, ExistenceRequirement::AllowDeath);
+            if refunded.is_err() {
+                Self::deposit_event(RawEvent::RefundFailed(tournament_id, kitty_id, entrant));
+            }
+        }
+        Tournaments::<T>::remove(tournament_id);
+        OpenTournaments::mutate(|open| *open = open.saturating_sub(1));
+        Self::deposit_event(RawEvent::TournamentCancelled(tournament_id));
+    }
+
+    /// Seed a tournament's bracket, or call it off if it has fewer than two entrants.
+    fn start_tournament(tournament_id: u32) {
+        let tournament = match Self::tournament(tournament_id) {
+            Some(tournament) => tournament,
+            None => return,
+        };
+        let mut bracket = Self::bracket(tournament_id);
+        if bracket.round.len() < 2 {
+            Self::call_off(tournament_id, &tournament);
+            return;
+        }
+
+        // Shuffle the entries so that registering early or late buys no advantage.
+        let seed = Self::random_seed(tournament_id);
+        for i in (1..bracket.round.len()).rev() {
+            let draw = T::Hashing::hash_of(&(seed, i as u32));
+            let draw = draw.as_ref();
+            let j = u32::from_le_bytes([draw[0], draw[1], draw[2], draw[3]]) as usize % (i + 1);
+            bracket.round.swap(i, j);
+        }
+
+        let entrants = bracket.round.len() as u32;
+        Brackets::<T>::insert(tournament_id, bracket);
+        RunningTournaments::append(tournament_id);
+        Self::deposit_event(RawEvent::TournamentStarted(tournament_id, entrants));
+    }
+
+    /// Play the running tournaments' matches, at most one round per tournament and at most
+    /// `MaxTournamentMatches` matches in all, returning the number of matches played.
+    ///
+    /// Each match is a battle between neighbours in the bracket, and an entry left without an
+    /// opponent advances unopposed. Tournaments that crown a champion pay out their prizes.
+    fn play_matches() -> u32 {
+        let max = T::MaxTournamentMatches::get();
+        let mut played = 0;
+        let mut running = Vec::new();
+        let mut waiting = Self::running_tournaments().into_iter();
+
+        while let Some(tournament_id) = waiting.next() {
+            if played >= max {
+                // Tournaments that got no matches this block go first next block, unread.
+                let mut next = Vec::new();
+                next.push(tournament_id);
+                next.extend(waiting);
+                next.append(&mut running);
+                running = next;
+                break;
+            }
+            let mut bracket = Self::bracket(tournament_id);
+            while played < max {
+                let (entry, other) = match (bracket.round.pop(), bracket.round.pop()) {
+                    (Some(entry), Some(other)) => (entry, other),
+                    (Some(entry), None) => {
+                        bracket.advancing.push(entry);
+                        break;
+                    },
+                    _ => break,
+                };
+                let power = Self::power_of(&entry.0).unwrap_or_default();
+                let other_power = Self::power_of(&other.0).unwrap_or_default();
+                let seed = Self::random_seed((tournament_id, entry.0, other.0));
+                let (winner, loser) = if battle::wins(power, other_power, seed.as_ref()) {
+                    (entry, other)
+                } else {
+                    (other, entry)
+                };
+
+                Self::record_battle(&winner.0, &loser.0);
+                Self::deposit_event(RawEvent::MatchWon(tournament_id, winner.0, loser.0));
+                bracket.advancing.push(winner);
+                bracket.eliminated.push(loser);
+                played += 1;
+            }
+
+            if bracket.round.is_empty() {
+                bracket.round = sp_std::mem::take(&mut bracket.advancing);
+            }
+            if bracket.round.len() < 2 && bracket.advancing.is_empty() {
+                Self::award_prizes(tournament_id, bracket);
+            } else {
+                Brackets::<T>::insert(tournament_id, bracket);
+                running.push(tournament_id);
+            }
+        }
+
+        RunningTournaments::put(running);
+        played
+    }
+
+    /// Pay out a finished tournament's prize pool and remove it.
+    ///
+    /// The champion is followed by the entries knocked out last, so the runner-up is the
+    /// champion's opponent in the final. A prize that cannot be paid goes to the champion instead.
+    fn award_prizes(tournament_id: u32, bracket: BracketOf<T>) {
+        let pool = Self::tournament_account(tournament_id);
+        let purse = T::Currency::free_balance(&pool);
+        let mut finishers = bracket.round.into_iter().chain(bracket.eliminated.into_iter().rev());
+
+        if let Some((champion_id, champion)) = finishers.next() {
+            let prizes = T::TournamentPrizes::get();
+            for (place, ((kitty_id, entrant), share)) in finishers.zip(prizes.iter().skip(1)).enumerate() {
+                let prize = *share * purse;
+                if T::Currency::transfer(&pool, &entrant, prize, ExistenceRequirement::AllowDeath).is_ok() {
+                    let place = place as u32 + 2;
+                    Self::deposit_event(RawEvent::PrizePaid(tournament_id, kitty_id, entrant, place, prize));
+                }
+            }
+
+            let prize = T::Currency::free_balance(&pool);
+            if T::Currency::transfer(&pool, &champion, prize, ExistenceRequirement::AllowDeath).is_ok() {
+                Self::deposit_event(RawEvent::PrizePaid(tournament_id, champion_id, champion, 1, prize));
+            }
+        }
+        Brackets::<T>::remove(tournament_id);
+        Tournaments::<T>::remove(tournament_id);
+        OpenTournaments::mutate(|open| *open = open.saturating_sub(1));
+    }
+
+    /// Sell an auctioned kitty to the high bidder.
+    ///
+    /// If there is no high bidder, or the sale fails because the seller no longer owns the kitty
+    /// or the bidder cannot cover the funds it locks, the auction ends unsold and the bid is
+    /// returned.
+    fn settle(kitty_id: &T::Hash) {
+        let auction = match Auctions::<T>::take(kitty_id) {
+            Some(auction) => auction,
+            None => return,
+        };
+
+        if let Some((bidder, bid)) = auction.high_bid {
+            let sold = T::Kitties::owner_of(kitty_id) == auction.seller
+                && Self::do_sale(&auction.seller, &bidder, kitty_id, bid, Funds::Reserved).is_ok();
+            if sold {
+                return;
+            }
+            T::Currency::unreserve(&bidder, bid);
+        }
 
-        // TODO: RELEASE
-        // burn kitty and unlock funds
+        Self::deposit_event(RawEvent::AuctionUnsold(*kitty_id));
     }
 }
@@ -1,19 +1,32 @@
 // Creating mock runtime here
 
-// use crate::{Module, Trait};
-use frame_support::{impl_outer_origin, parameter_types, weights::Weight};
+use crate::{KittyInfo, Module, Mutation, Slot, Trait};
+use frame_support::{impl_outer_event, impl_outer_origin, parameter_types, traits::Randomness, weights::Weight};
 use frame_system as system;
 use sp_core::H256;
 use sp_runtime::{
     testing::Header,
-    traits::{BlakeTwo256, IdentityLookup},
-    Perbill,
+    traits::{BlakeTwo256, Hash, IdentityLookup},
+    ModuleId, Perbill,
 };
 
 impl_outer_origin! {
     pub enum Origin for Test {}
 }
 
+mod substratekitties {
+    pub use crate::Event;
+}
+
+impl_outer_event! {
+    pub enum TestEvent for Test {
+        system<T>,
+        pallet_balances<T>,
+        pallet_commodities<T>,
+        substratekitties<T>,
+    }
+}
+
 // For testing the pallet, we construct most of a mock runtime. This means
 // first constructing a configuration type (`Test`) which `impl`s each of the
 // configuration traits of pallets we want to use.
@@ -36,7 +49,7 @@ impl system::Trait for Test {
     type AccountId = u64;
     type Lookup = IdentityLookup<Self::AccountId>;
     type Header = Header;
-    type Event = ();
+    type Event = TestEvent;
     type BlockHashCount = BlockHashCount;
     type MaximumBlockWeight = MaximumBlockWeight;
     type DbWeight = ();
@@ -47,20 +60,132 @@ impl system::Trait for Test {
     type AvailableBlockRatio = AvailableBlockRatio;
     type Version = ();
     type PalletInfo = ();
-    type AccountData = ();
+    type AccountData = pallet_balances::AccountData<u64>;
     type OnNewAccount = ();
     type OnKilledAccount = ();
     type SystemWeightInfo = ();
 }
 
-// impl Trait for Test {
-// 	type Event = ();
-// }
+parameter_types! {
+    pub const ExistentialDeposit: u64 = 1;
+    pub const MaxLocks: u32 = 50;
+}
+impl pallet_balances::Trait for Test {
+    type MaxLocks = MaxLocks;
+    type Balance = u64;
+    type Event = TestEvent;
+    type DustRemoval = ();
+    type ExistentialDeposit = ExistentialDeposit;
+    type AccountStore = System;
+    type WeightInfo = ();
+}
 
-// pub type TemplateModule = Module<Test>;
+parameter_types! {
+    pub const MinimumPeriod: u64 = 1;
+}
+impl pallet_timestamp::Trait for Test {
+    type Moment = u64;
+    type OnTimestampSet = ();
+    type MinimumPeriod = MinimumPeriod;
+    type WeightInfo = ();
+}
+
+parameter_types! {
+    pub const MaxKitties: u128 = 1_000;
+    pub const MaxKittiesPerUser: u64 = 100;
+}
+impl pallet_commodities::Trait for Test {
+    type CommodityAdmin = system::EnsureRoot<u64>;
+    type CommodityInfo = KittyInfo<H256, u64>;
+    type CommodityLimit = MaxKitties;
+    type UserCommodityLimit = MaxKittiesPerUser;
+    type Event = TestEvent;
+}
+
+// Randomness that depends only on its subject, so tests are deterministic.
+pub struct TestRandomness;
+impl Randomness<H256> for TestRandomness {
+    fn random(subject: &[u8]) -> H256 {
+        BlakeTwo256::hash(subject)
+    }
+}
+
+parameter_types! {
+    pub const BasePrice: u64 = 100;
+    pub const FlirtPeriod: u64 = 10;
+    pub const MaxIntentPrunes: u32 = 2;
+    pub const MaxPedigreeDepth: u32 = 4;
+    pub const MaxInbreedingDepth: u32 = 2;
+    pub CooldownSchedule: Vec<u64> = vec![5, 10];
+    pub const MinBreedingAge: u64 = 1_000;
+    pub const MaxAuctionSettlements: u32 = 2;
+    pub const OfferPeriod: u64 = 10;
+    pub const MaxOfferRefunds: u32 = 2;
+    pub const MaxOffersPerKitty: u32 = 2;
+    pub const MaxRentalReturns: u32 = 2;
+    pub const MaxBattles: u32 = 2;
+    pub const LeaderboardSize: u32 = 2;
+    pub const MaxTournamentSize: u32 = 4;
+    pub const MaxRunningTournaments: u32 = 2;
+    pub const MaxTournamentMatches: u32 = 2;
+    pub TournamentPrizes: Vec<Perbill> = vec![Perbill::from_percent(70), Perbill::from_percent(30)];
+    pub const RoyaltyPercent: Perbill = Perbill::from_percent(10);
+    pub const TreasuryId: ModuleId = ModuleId(*b"kit/trsy");
+    pub const TournamentId: ModuleId = ModuleId(*b"kit/trnm");
+    pub Mutations: Vec<Mutation> = vec![Mutation {
+        slot: Slot::Body,
+        alleles: (3, 7),
+        result: 14,
+        chance: Perbill::from_percent(100),
+    }];
+}
+impl Trait for Test {
+    type Kitties = Commodities;
+    type Time = Timestamp;
+    type Randomness = TestRandomness;
+    type Currency = Balances;
+    type BasePrice = BasePrice;
+    type FlirtPeriod = FlirtPeriod;
+    type MaxIntentPrunes = MaxIntentPrunes;
+    type MaxPedigreeDepth = MaxPedigreeDepth;
+    type MaxInbreedingDepth = MaxInbreedingDepth;
+    type CooldownSchedule = CooldownSchedule;
+    type MinBreedingAge = MinBreedingAge;
+    type Mutations = Mutations;
+    type MaxAuctionSettlements = MaxAuctionSettlements;
+    type OfferPeriod = OfferPeriod;
+    type MaxOfferRefunds = MaxOfferRefunds;
+    type MaxOffersPerKitty = MaxOffersPerKitty;
+    type RoyaltyPercent = RoyaltyPercent;
+    type TreasuryId = TreasuryId;
+    type MaxRentalReturns = MaxRentalReturns;
+    type MaxBattles = MaxBattles;
+    type LeaderboardSize = LeaderboardSize;
+    type TournamentId = TournamentId;
+    type MaxTournamentSize = MaxTournamentSize;
+    type MaxRunningTournaments = MaxRunningTournaments;
+    type MaxTournamentMatches = MaxTournamentMatches;
+    type TournamentPrizes = TournamentPrizes;
+    type Event = TestEvent;
+}
+
+pub type System = system::Module<Test>;
+pub type Balances = pallet_balances::Module<Test>;
+pub type Timestamp = pallet_timestamp::Module<Test>;
+pub type Commodities = pallet_commodities::Module<Test>;
+pub type Substratekitties = Module<Test>;
 
 // This function basically just builds a genesis storage key/value store according to
 // our desired mockup.
-// pub fn new_test_ext() -> sp_io::TestExternalities {
-// 	system::GenesisConfig::default().build_storage::<Test>().unwrap().into()
-// }
+pub fn new_test_ext() -> sp_io::TestExternalities {
+    let mut t = system::GenesisConfig::default().build_storage::<Test>().unwrap();
+    pallet_balances::GenesisConfig::<Test> {
+        balances: vec![(1, 10_000), (2, 10_000), (3, 10_000), (4, 10_000)],
+    }
+    .assimilate_storage(&mut t)
+    .unwrap();
+
+    let mut ext: sp_io::TestExternalities = t.into();
+    ext.execute_with(|| System::set_block_number(1));
+    ext
+}
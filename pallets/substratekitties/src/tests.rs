@@ -1,15 +1,759 @@
 // Tests to be written here
 
-// use crate::{Error, mock::*};
-// use frame_support::{assert_ok, assert_noop};
-
-// #[test]
-// fn it_works_for_default_value() {
-// 	new_test_ext().execute_with(|| {
-// 		// Just a dummy test for the dummy function `do_something`
-// 		// calling the `do_something` function with a value 42
-// 		assert_ok!(TemplateModule::do_something(Origin::signed(1), 42));
-// 		// asserting that the stored value is equal to what we stored
-// 		assert_eq!(TemplateModule::something(), Some(42));
-// 	});
-// }
+use crate::{battle, genome, mock::*, BattleRecord, Error, Event, KittyGenome, LineageForKitty, RawEvent, Slot};
+use frame_support::{assert_noop, assert_ok, traits::OnInitialize};
+use pallet_commodities::nft::UniqueAssets;
+use sp_core::H256;
+use sp_runtime::Perbill;
+use sp_std::collections::btree_set::BTreeSet;
+
+fn kitty_ids(owner: u64) -> Vec<H256> {
+    Commodities::assets_for_account(&owner)
+        .into_iter()
+        .map(|(id, _)| id)
+        .collect()
+}
+
+fn kitty_events() -> Vec<Event<Test>> {
+    System::events()
+        .into_iter()
+        .filter_map(|record| match record.event {
+            TestEvent::substratekitties(event) => Some(event),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Conjure a kitty for account 1 and a partner for account 2, boost both and make them old enough
+/// to breed.
+fn breeding_pair() -> (H256, H256) {
+    let kitty = conjure_kitty(1);
+    let partner = conjure_kitty(2);
+    assert_ok!(Substratekitties::boost(Origin::signed(1), kitty, 50));
+    assert_ok!(Substratekitties::boost(Origin::signed(2), partner, 50));
+    Timestamp::set_timestamp(MinBreedingAge::get());
+    (kitty, partner)
+}
+
+fn set_parents(kitty: H256, parents: (u8, u8)) {
+    let parents = (H256::repeat_byte(parents.0), H256::repeat_byte(parents.1));
+    LineageForKitty::<Test>::mutate(kitty, |lineage| lineage.parents = Some(parents));
+}
+
+fn conjure_kitty(owner: u64) -> H256 {
+    let before = kitty_ids(owner);
+    assert_ok!(Substratekitties::conjure(Origin::signed(owner), b"kitty".to_vec()));
+    kitty_ids(owner)
+        .into_iter()
+        .find(|id| !before.contains(id))
+        .unwrap()
+}
+
+#[test]
+fn conjure_locks_base_price_per_kitty() {
+    new_test_ext().execute_with(|| {
+        for _ in 0..3 {
+            assert_ok!(Substratekitties::conjure(Origin::signed(1), b"kitty".to_vec()));
+        }
+
+        assert_eq!(Substratekitties::locked_for_account(1), 3 * BasePrice::get());
+    });
+}
+
+#[test]
+fn locked_funds_cannot_be_transferred_out() {
+    new_test_ext().execute_with(|| {
+        conjure_kitty(1);
+        assert_noop!(
+            Balances::transfer(Origin::signed(1), 2, 10_000 - BasePrice::get() + 1),
+            pallet_balances::Error::<Test, _>::LiquidityRestrictions
+        );
+        assert_ok!(Balances::transfer(Origin::signed(1), 2, 10_000 - BasePrice::get()));
+        assert_eq!(Balances::free_balance(1), BasePrice::get());
+    });
+}
+
+#[test]
+fn transfer_moves_the_stake_to_the_recipient() {
+    new_test_ext().execute_with(|| {
+        let kitty = conjure_kitty(1);
+        assert_ok!(Substratekitties::boost(Origin::signed(1), kitty, 50));
+        assert_noop!(
+            Substratekitties::transfer(Origin::signed(1), 5, kitty),
+            Error::<Test>::InsufficientBalance
+        );
+        assert_eq!(Commodities::owner_of(&kitty), 1);
+
+        assert_ok!(Substratekitties::transfer(Origin::signed(1), 2, kitty));
+        assert_eq!(Commodities::owner_of(&kitty), 2);
+        assert_eq!(Substratekitties::boost_for_kitty(kitty), 50);
+        assert_eq!(Substratekitties::locked_for_account(1), 0);
+        assert_eq!(Substratekitties::locked_for_account(2), BasePrice::get() + 50);
+        assert_eq!(kitty_events().last(), Some(&RawEvent::Transferred(kitty, 1, 2)));
+    });
+}
+
+#[test]
+fn conjure_in_one_block_gives_distinct_kitties() {
+    new_test_ext().execute_with(|| {
+        for _ in 0..5 {
+            assert_ok!(Substratekitties::conjure(Origin::signed(1), b"kitty".to_vec()));
+        }
+        assert_ok!(Substratekitties::conjure(Origin::signed(2), b"kitty".to_vec()));
+
+        let mut kitties = Commodities::assets_for_account(&1);
+        kitties.extend(Commodities::assets_for_account(&2));
+        assert_eq!(kitties.len(), 6);
+
+        let ids: BTreeSet<_> = kitties.iter().map(|(id, _)| *id).collect();
+        let dnas: BTreeSet<_> = kitties.iter().map(|(_, info)| info.dna).collect();
+        assert_eq!(ids.len(), 6);
+        assert_eq!(dnas.len(), 6);
+    });
+}
+
+#[test]
+fn genome_decodes_like_the_avatar() {
+    let mut dna = [0u8; 32];
+    dna[..4].copy_from_slice(&[0x12, 0x34, 0x58, 0x7f]);
+    dna[31] = 0x03;
+
+    let genome = KittyGenome::from_dna(&dna);
+    assert_eq!(genome.body, 1);
+    assert_eq!(genome.eyes, 2);
+    assert_eq!(genome.accessory, 3);
+    assert_eq!(genome.pattern, 4);
+    assert_eq!(genome.mouth, 5);
+    assert_eq!(genome.snack, Some(1));
+    assert_eq!(genome.power, 4);
+}
+
+#[test]
+fn recoup_unlocks_only_the_kittys_boost() {
+    new_test_ext().execute_with(|| {
+        let kitty = conjure_kitty(1);
+        let other = conjure_kitty(1);
+        assert_ok!(Substratekitties::boost(Origin::signed(1), kitty, 100));
+        assert_ok!(Substratekitties::boost(Origin::signed(1), other, 50));
+        assert_eq!(Substratekitties::locked_for_account(1), 2 * BasePrice::get() + 150);
+
+        assert_ok!(Substratekitties::recoup(Origin::signed(1), kitty, 30));
+        assert_eq!(Substratekitties::boost_for_kitty(kitty), 70);
+        assert_eq!(Substratekitties::locked_for_account(1), 2 * BasePrice::get() + 120);
+
+        assert_ok!(Substratekitties::recoup(Origin::signed(1), kitty, 1_000));
+        assert_eq!(Substratekitties::boost_for_kitty(kitty), 0);
+        assert_eq!(Substratekitties::boost_for_kitty(other), 50);
+        assert_eq!(Substratekitties::locked_for_account(1), 2 * BasePrice::get() + 50);
+        assert_noop!(Substratekitties::recoup(Origin::signed(1), kitty, 10), Error::<Test>::NoBoost);
+
+        let events = kitty_events();
+        assert!(events.contains(&RawEvent::Boosted(kitty, 1, 100)));
+        assert!(events.contains(&RawEvent::Recouped(kitty, 1, 30)));
+        assert!(events.contains(&RawEvent::Recouped(kitty, 1, 70)));
+    });
+}
+
+#[test]
+fn release_unlocks_the_stake_and_clears_the_kitty() {
+    new_test_ext().execute_with(|| {
+        let kitty = conjure_kitty(1);
+        conjure_kitty(1);
+        assert_ok!(Substratekitties::boost(Origin::signed(1), kitty, 50));
+        assert_ok!(Substratekitties::sell(Origin::signed(1), kitty, 500));
+        assert_ok!(Substratekitties::flirt(Origin::signed(1), kitty, None));
+        assert_ok!(Substratekitties::make_offer(Origin::signed(2), kitty, 300));
+        assert_eq!(Substratekitties::locked_for_account(1), 2 * BasePrice::get() + 50);
+        assert_noop!(Substratekitties::release(Origin::signed(2), kitty), Error::<Test>::NotKittyOwner);
+
+        assert_ok!(Substratekitties::release(Origin::signed(1), kitty));
+        assert_eq!(Substratekitties::locked_for_account(1), BasePrice::get());
+        assert_eq!(kitty_ids(1).len(), 1);
+        assert_eq!(Substratekitties::metadata_for_kitty(kitty), Default::default());
+        assert_eq!(Substratekitties::boost_for_kitty(kitty), 0);
+        assert_eq!(Substratekitties::listing(kitty), None);
+        assert_eq!(Substratekitties::breeding_intent(kitty), None);
+        assert_eq!(Substratekitties::offer(kitty, 2), None);
+        assert_eq!(Balances::reserved_balance(2), 0);
+    });
+}
+
+#[test]
+fn breeding_records_lineage() {
+    new_test_ext().execute_with(|| {
+        let (kitty, partner) = breeding_pair();
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), partner, None));
+        assert_ok!(Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()));
+
+        let child = Substratekitties::children_of(&kitty)[0];
+        assert_eq!(Substratekitties::children_of(&partner), vec![child]);
+        let lineage = Substratekitties::lineage_for_kitty(child);
+        assert_eq!(lineage.parents, Some((kitty, partner)));
+        assert_eq!(lineage.generation, 1);
+        let owner = Commodities::owner_of(&child);
+        assert_eq!(kitty_events().last(), Some(&RawEvent::Bred(child, kitty, partner, owner)));
+        assert_eq!(Substratekitties::ancestors(&child, 4), vec![(kitty, 1), (partner, 1)]);
+    });
+}
+
+#[test]
+fn breeding_requires_age_and_rest() {
+    new_test_ext().execute_with(|| {
+        let (kitty, partner) = breeding_pair();
+        Timestamp::set_timestamp(MinBreedingAge::get() - 1);
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), partner, None));
+        assert_noop!(
+            Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()),
+            Error::<Test>::KittyTooYoung
+        );
+
+        Timestamp::set_timestamp(MinBreedingAge::get());
+        assert_ok!(Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()));
+        assert_eq!(Substratekitties::ready_at(kitty), 1 + CooldownSchedule::get()[0]);
+
+        // Breeding may have used up the parents' boosts.
+        assert_ok!(Substratekitties::boost(Origin::signed(1), kitty, 50));
+        assert_ok!(Substratekitties::boost(Origin::signed(2), partner, 50));
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), partner, None));
+        assert_noop!(
+            Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()),
+            Error::<Test>::KittyCoolingDown
+        );
+    });
+}
+
+#[test]
+fn expired_intents_are_pruned_a_few_per_block() {
+    new_test_ext().execute_with(|| {
+        let kitties: Vec<_> = (0..3).map(|_| conjure_kitty(1)).collect();
+        for kitty in &kitties {
+            assert_ok!(Substratekitties::boost(Origin::signed(1), *kitty, 50));
+            assert_ok!(Substratekitties::flirt(Origin::signed(1), *kitty, None));
+        }
+        let reposted = conjure_kitty(2);
+        assert_ok!(Substratekitties::boost(Origin::signed(2), reposted, 50));
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), reposted, None));
+        System::set_block_number(5);
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), reposted, None));
+
+        let expires = 1 + FlirtPeriod::get();
+        System::set_block_number(expires);
+        Substratekitties::on_initialize(expires);
+        let open = kitties.iter().filter(|kitty| Substratekitties::breeding_intent(**kitty).is_some());
+        assert_eq!(open.count(), 3 - MaxIntentPrunes::get() as usize);
+        assert!(Substratekitties::breeding_intent(reposted).is_some());
+
+        System::set_block_number(expires + 1);
+        Substratekitties::on_initialize(expires + 1);
+        assert!(kitties.iter().all(|kitty| Substratekitties::breeding_intent(*kitty).is_none()));
+        assert_eq!(Substratekitties::breeding_intent(reposted).unwrap().expires, 5 + FlirtPeriod::get());
+    });
+}
+
+#[test]
+fn intents_end_when_cancelled_or_the_kitty_moves() {
+    new_test_ext().execute_with(|| {
+        let (kitty, partner) = breeding_pair();
+
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), partner, None));
+        assert_noop!(
+            Substratekitties::cancel_flirt(Origin::signed(1), partner),
+            Error::<Test>::NotKittyOwner
+        );
+        assert_ok!(Substratekitties::cancel_flirt(Origin::signed(2), partner));
+        assert_eq!(Substratekitties::breeding_intent(partner), None);
+        assert_noop!(
+            Substratekitties::cancel_flirt(Origin::signed(2), partner),
+            Error::<Test>::NoBreedingIntent
+        );
+        assert_noop!(
+            Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()),
+            Error::<Test>::NoBreedingIntent
+        );
+
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), partner, None));
+        assert_ok!(Substratekitties::transfer(Origin::signed(2), 3, partner));
+        assert_eq!(Substratekitties::breeding_intent(partner), None);
+        assert_noop!(
+            Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()),
+            Error::<Test>::NoBreedingIntent
+        );
+    });
+}
+
+#[test]
+fn crossing_is_deterministic_and_mutates() {
+    // Body alleles 3/3 and 7/7.
+    let mut dna = [0u8; 32];
+    let mut other = [0u8; 32];
+    dna[0] = 0x30;
+    dna[16] = 0x30;
+    other[0] = 0x70;
+    other[16] = 0x70;
+    let seed = [1u8; 32];
+
+    let mut child = [0u8; 32];
+    genome::cross(&dna, &other, &seed, &[], &mut child);
+    let (dominant, recessive) = genome::alleles(&child, Slot::Body);
+    let mut body = [dominant, recessive];
+    body.sort();
+    assert_eq!(body, [3, 7]);
+
+    let mut again = [0u8; 32];
+    genome::cross(&dna, &other, &seed, &[], &mut again);
+    assert_eq!(child, again);
+
+    let mut mutated = [0u8; 32];
+    genome::cross(&dna, &other, &seed, &Mutations::get(), &mut mutated);
+    assert_eq!(genome::alleles(&mutated, Slot::Body).0, 14);
+
+    let mut oversized = Mutations::get();
+    oversized[0].result = 16;
+    assert!(!oversized[0].is_valid());
+    let mut ignored = [0u8; 32];
+    genome::cross(&dna, &other, &seed, &oversized, &mut ignored);
+    assert_eq!(ignored, child);
+}
+
+#[test]
+fn parents_cannot_breed_with_offspring() {
+    new_test_ext().execute_with(|| {
+        let (kitty, partner) = breeding_pair();
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), partner, None));
+        assert_ok!(Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()));
+
+        let child = Substratekitties::children_of(&kitty)[0];
+        assert_ok!(Substratekitties::boost(Origin::signed(1), kitty, 50));
+        assert_ok!(Substratekitties::flirt(Origin::signed(1), kitty, None));
+        assert_noop!(
+            Substratekitties::breed(
+                Origin::signed(Commodities::owner_of(&child)),
+                child,
+                kitty,
+                b"kitten".to_vec()
+            ),
+            Error::<Test>::KittiesRelated
+        );
+    });
+}
+
+#[test]
+fn kitties_sharing_a_recent_ancestor_cannot_breed() {
+    new_test_ext().execute_with(|| {
+        let (kitty, partner) = breeding_pair();
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), partner, None));
+
+        // Siblings.
+        set_parents(kitty, (1, 2));
+        set_parents(partner, (1, 2));
+        assert_noop!(
+            Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()),
+            Error::<Test>::KittiesRelated
+        );
+
+        // Cousins, sharing grandparent 3.
+        set_parents(kitty, (10, 11));
+        set_parents(partner, (20, 21));
+        set_parents(H256::repeat_byte(10), (3, 12));
+        set_parents(H256::repeat_byte(20), (3, 22));
+        assert_noop!(
+            Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()),
+            Error::<Test>::KittiesRelated
+        );
+
+        // Second cousins, whose shared great-grandparent is past `MaxInbreedingDepth`.
+        assert_eq!(MaxInbreedingDepth::get(), 2);
+        set_parents(H256::repeat_byte(10), (13, 12));
+        set_parents(H256::repeat_byte(20), (23, 22));
+        set_parents(H256::repeat_byte(13), (3, 14));
+        set_parents(H256::repeat_byte(23), (3, 24));
+        assert_ok!(Substratekitties::breed(Origin::signed(1), kitty, partner, b"kitten".to_vec()));
+    });
+}
+
+#[test]
+fn auction_sells_to_high_bidder() {
+    new_test_ext().execute_with(|| {
+        let kitty = conjure_kitty(1);
+        assert_ok!(Substratekitties::start_auction(Origin::signed(1), kitty, 200, 50, 5));
+        assert_noop!(
+            Substratekitties::transfer(Origin::signed(1), 2, kitty),
+            Error::<Test>::KittyInAuction
+        );
+
+        assert_noop!(
+            Substratekitties::boost(Origin::signed(1), kitty, 50),
+            Error::<Test>::KittyInAuction
+        );
+        assert_noop!(
+            Substratekitties::bid(Origin::signed(2), kitty, 9_950),
+            Error::<Test>::InsufficientBalance
+        );
+
+        assert_ok!(Substratekitties::bid(Origin::signed(2), kitty, 200));
+        assert_noop!(
+            Substratekitties::bid(Origin::signed(3), kitty, 240),
+            Error::<Test>::BidTooLow
+        );
+        assert_ok!(Substratekitties::bid(Origin::signed(3), kitty, 250));
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::reserved_balance(3), 250);
+
+        Substratekitties::on_initialize(6);
+        assert_eq!(Commodities::owner_of(&kitty), 3);
+        assert_eq!(Substratekitties::auction(kitty), None);
+        assert_eq!(Balances::reserved_balance(3), 0);
+        assert_eq!(Balances::free_balance(3), 10_000 - 250);
+        assert_eq!(Balances::free_balance(1), 10_000 + 250);
+        assert_eq!(Substratekitties::locked_for_account(1), 0);
+        assert_eq!(Substratekitties::locked_for_account(3), BasePrice::get());
+    });
+}
+
+#[test]
+fn auctioned_kitties_cannot_breed() {
+    new_test_ext().execute_with(|| {
+        let (kitty, partner) = breeding_pair();
+        assert_ok!(Substratekitties::offer_sire(Origin::signed(1), kitty, 10));
+        assert_ok!(Substratekitties::flirt(Origin::signed(1), kitty, None));
+
+        assert_ok!(Substratekitties::start_auction(Origin::signed(1), kitty, 200, 50, 5));
+        assert_eq!(Substratekitties::sire_listing(kitty), None);
+        assert_eq!(Substratekitties::breeding_intent(kitty), None);
+
+        assert_ok!(Substratekitties::flirt(Origin::signed(1), kitty, None));
+        assert_noop!(
+            Substratekitties::breed(Origin::signed(2), partner, kitty, b"kitten".to_vec()),
+            Error::<Test>::KittyInAuction
+        );
+        assert_eq!(Substratekitties::boost_for_kitty(kitty), 50);
+    });
+}
+
+#[test]
+fn declining_price_falls_over_time() {
+    new_test_ext().execute_with(|| {
+        let kitty = conjure_kitty(1);
+        assert_ok!(Substratekitties::sell_declining(Origin::signed(1), kitty, 1_000, 200, 8));
+        assert_eq!(Substratekitties::current_price(&kitty), Some(1_000));
+
+        System::set_block_number(5);
+        assert_eq!(Substratekitties::current_price(&kitty), Some(600));
+        assert_noop!(
+            Substratekitties::buy(Origin::signed(2), kitty, 599),
+            Error::<Test>::PriceTooHigh
+        );
+        assert_ok!(Substratekitties::buy(Origin::signed(2), kitty, 600));
+        assert_eq!(Commodities::owner_of(&kitty), 2);
+        assert_eq!(Substratekitties::current_price(&kitty), None);
+
+        System::set_block_number(20);
+        assert_ok!(Substratekitties::sell_declining(Origin::signed(2), kitty, 1_000, 200, 8));
+        System::set_block_number(100);
+        assert_eq!(Substratekitties::current_price(&kitty), Some(200));
+    });
+}
+
+#[test]
+fn siring_fee_goes_to_sire_owner_and_offspring_to_payer() {
+    new_test_ext().execute_with(|| {
+        let (kitty, sire) = breeding_pair();
+        assert_ok!(Substratekitties::offer_sire(Origin::signed(2), sire, 300));
+
+        assert_noop!(
+            Substratekitties::breed_with_sire(Origin::signed(1), kitty, sire, 299, b"kitten".to_vec()),
+            Error::<Test>::PriceTooHigh
+        );
+        assert_ok!(Substratekitties::breed_with_sire(
+            Origin::signed(1),
+            kitty,
+            sire,
+            300,
+            b"kitten".to_vec()
+        ));
+
+        let child = Substratekitties::children_of(&sire)[0];
+        assert_eq!(Commodities::owner_of(&child), 1);
+        assert_eq!(Commodities::owner_of(&sire), 2);
+        assert_eq!(Balances::free_balance(1), 10_000 - 300);
+        assert_eq!(Balances::free_balance(2), 10_000 + 300);
+    });
+}
+
+#[test]
+fn offers_are_escrowed_until_accepted_or_expired() {
+    new_test_ext().execute_with(|| {
+        let kitty = conjure_kitty(1);
+        assert_ok!(Substratekitties::make_offer(Origin::signed(2), kitty, 400));
+        assert_ok!(Substratekitties::make_offer(Origin::signed(3), kitty, 300));
+        assert_eq!(Balances::reserved_balance(2), 400);
+        assert_noop!(
+            Substratekitties::make_offer(Origin::signed(4), kitty, 300),
+            Error::<Test>::OfferTooLow
+        );
+        assert_ok!(Substratekitties::make_offer(Origin::signed(4), kitty, 320));
+        assert_eq!(Substratekitties::offer(kitty, 3), None);
+        assert_eq!(Balances::reserved_balance(3), 0);
+        assert_ok!(Substratekitties::make_offer(Origin::signed(3), kitty, 350));
+        assert_eq!(Balances::reserved_balance(4), 0);
+        assert_eq!(Substratekitties::offer_count(kitty), 2);
+
+        assert_noop!(
+            Substratekitties::accept_offer(Origin::signed(1), kitty, 2, 500),
+            Error::<Test>::OfferTooLow
+        );
+        assert_ok!(Substratekitties::accept_offer(Origin::signed(1), kitty, 2, 400));
+        assert_eq!(Commodities::owner_of(&kitty), 2);
+        assert_eq!(Balances::reserved_balance(2), 0);
+        assert_eq!(Balances::free_balance(1), 10_000 + 400);
+
+        Substratekitties::on_initialize(1 + OfferPeriod::get());
+        assert_eq!(Substratekitties::offer(kitty, 3), None);
+        assert_eq!(Balances::reserved_balance(3), 0);
+    });
+}
+
+#[test]
+fn resales_pay_royalties_to_creator() {
+    new_test_ext().execute_with(|| {
+        let kitty = conjure_kitty(1);
+        assert_ok!(Substratekitties::sell(Origin::signed(1), kitty, 1_000));
+        assert_ok!(Substratekitties::buy(Origin::signed(2), kitty, 1_000));
+        assert_eq!(Balances::free_balance(1), 10_000 + 1_000);
+
+        assert_ok!(Substratekitties::sell(Origin::signed(2), kitty, 500));
+        assert_ok!(Substratekitties::buy(Origin::signed(3), kitty, 500));
+        assert_eq!(Balances::free_balance(1), 10_000 + 1_000 + 50);
+        assert_eq!(Balances::free_balance(2), 10_000 - 1_000 + 450);
+        assert_eq!(Balances::free_balance(3), 10_000 - 500);
+        assert!(kitty_events().contains(&RawEvent::RoyaltyPaid(kitty, 1, 50)));
+    });
+}
+
+#[test]
+fn sales_pay_market_fee_to_treasury() {
+    new_test_ext().execute_with(|| {
+        assert_noop!(
+            Substratekitties::set_market_fee(Origin::signed(1), Perbill::from_percent(2)),
+            sp_runtime::DispatchError::BadOrigin
+        );
+        assert_noop!(
+            Substratekitties::set_market_fee(Origin::root(), Perbill::from_percent(91)),
+            Error::<Test>::FeeTooHigh
+        );
+        assert_ok!(Substratekitties::set_market_fee(Origin::root(), Perbill::from_percent(2)));
+
+        let kitty = conjure_kitty(1);
+        assert_ok!(Substratekitties::sell(Origin::signed(1), kitty, 900));
+        assert_ok!(Substratekitties::cancel_sale(Origin::signed(1), kitty));
+        assert_ok!(Substratekitties::sell(Origin::signed(1), kitty, 1_000));
+        assert_ok!(Substratekitties::buy(Origin::signed(2), kitty, 1_000));
+        assert_eq!(Balances::free_balance(Substratekitties::treasury_account()), 20);
+        assert_eq!(Balances::free_balance(1), 10_000 + 980);
+
+        let events = kitty_events();
+        assert!(events.contains(&RawEvent::Delisted(kitty)));
+        assert!(events.contains(&RawEvent::Listed(kitty, 1, 1_000)));
+        assert!(events.contains(&RawEvent::RoyaltyPaid(kitty, 1, 100)));
+        assert_eq!(events.last(), Some(&RawEvent::Sold(kitty, 1, 2, 1_000, 20)));
+    });
+}
+
+#[test]
+fn approvals_allow_transfers_until_the_kitty_moves() {
+    new_test_ext().execute_with(|| {
+        let kitty = conjure_kitty(1);
+        assert_noop!(
+            Substratekitties::transfer_from(Origin::signed(2), 1, 2, kitty),
+            Error::<Test>::NotApproved
+        );
+
+        assert_ok!(Substratekitties::approve(Origin::signed(1), kitty, Some(2)));
+        assert_ok!(Substratekitties::transfer_from(Origin::signed(2), 1, 3, kitty));
+        assert_eq!(Commodities::owner_of(&kitty), 3);
+        assert_eq!(Substratekitties::approval(kitty), None);
+        assert_noop!(
+            Substratekitties::transfer_from(Origin::signed(2), 3, 2, kitty),
+            Error::<Test>::NotApproved
+        );
+
+        assert_ok!(Substratekitties::set_approval_for_all(Origin::signed(3), 1, true));
+        assert_ok!(Substratekitties::transfer_from(Origin::signed(1), 3, 1, kitty));
+        assert_eq!(Commodities::owner_of(&kitty), 1);
+    });
+}
+
+#[test]
+fn rented_kitties_breed_for_the_borrower_and_return_on_time() {
+    new_test_ext().execute_with(|| {
+        let (kitty, partner) = breeding_pair();
+        assert_ok!(Substratekitties::offer_rental(Origin::signed(1), kitty, 200, 5));
+        assert_ok!(Substratekitties::rent(Origin::signed(2), kitty, 200));
+        assert_eq!(Balances::free_balance(1), 10_000 + 200);
+        assert_eq!(Substratekitties::user_of(&kitty), 2);
+
+        assert_ok!(Substratekitties::flirt(Origin::signed(2), partner, None));
+        assert_ok!(Substratekitties::breed(Origin::signed(2), kitty, partner, b"kitten".to_vec()));
+        let child = Substratekitties::children_of(&kitty)[0];
+        assert_eq!(Commodities::owner_of(&child), 2);
+        assert_eq!(Substratekitties::boost_for_kitty(kitty), 50);
+        assert_eq!(Substratekitties::locked_for_account(1), BasePrice::get() + 50);
+
+        assert_noop!(
+            Substratekitties::sell(Origin::signed(1), kitty, 1_000),
+            Error::<Test>::KittyRented
+        );
+        assert_noop!(
+            Substratekitties::transfer(Origin::signed(1), 3, kitty),
+            Error::<Test>::KittyRented
+        );
+        assert_noop!(Substratekitties::release(Origin::signed(1), kitty), Error::<Test>::KittyRented);
+        assert_noop!(
+            Substratekitties::recoup(Origin::signed(1), kitty, 10),
+            Error::<Test>::KittyRented
+        );
+        assert_noop!(
+            Substratekitties::offer_sire(Origin::signed(1), kitty, 10),
+            Error::<Test>::NotKittyOwner
+        );
+        assert_ok!(Substratekitties::offer_sire(Origin::signed(2), kitty, 10));
+
+        System::set_block_number(6);
+        Substratekitties::on_initialize(6);
+        assert_eq!(Substratekitties::rental(kitty), None);
+        assert_eq!(Substratekitties::sire_listing(kitty), None);
+        assert_eq!(Substratekitties::user_of(&kitty), 1);
+        assert_eq!(Commodities::owner_of(&kitty), 1);
+        assert_ok!(Substratekitties::release(Origin::signed(1), kitty));
+    });
+}
+
+#[test]
+fn battles_favour_power_and_pay_the_winner() {
+    new_test_ext().execute_with(|| {
+        assert!(!battle::wins(0, 10, &[0; 32]));
+        assert!(battle::wins(10, 0, &[0xff; 32]));
+        let wins = (0..=255u8).filter(|byte| battle::wins(3, 1, &[*byte; 32])).count();
+        assert!(wins > 128);
+
+        let kitty = conjure_kitty(1);
+        let opponent = conjure_kitty(2);
+        assert_noop!(
+            Substratekitties::challenge(Origin::signed(1), kitty, kitty, 100),
+            Error::<Test>::CannotBattleSelf
+        );
+        assert_ok!(Substratekitties::challenge(Origin::signed(1), kitty, opponent, 100));
+        assert_eq!(Balances::reserved_balance(1), 100);
+        assert_noop!(Substratekitties::accept(Origin::signed(3), kitty), Error::<Test>::NotKittyOwner);
+
+        assert_ok!(Substratekitties::accept(Origin::signed(2), kitty));
+        assert_eq!(Substratekitties::battle_record(kitty), BattleRecord::default());
+        assert_eq!(Balances::reserved_balance(2), 100);
+        assert_noop!(
+            Substratekitties::cancel_challenge(Origin::signed(1), kitty),
+            Error::<Test>::ChallengeAccepted
+        );
+        assert_noop!(Substratekitties::accept(Origin::signed(2), kitty), Error::<Test>::ChallengeAccepted);
+
+        System::set_block_number(2);
+        Substratekitties::on_initialize(2);
+        assert_eq!(Substratekitties::challenge(kitty), None);
+        let won = Substratekitties::battle_record(kitty) == BattleRecord{wins: 1, losses: 0};
+        let (winner, loser, loser_id) = if won { (1, 2, opponent) } else { (2, 1, kitty) };
+        assert_eq!(Substratekitties::battle_record(loser_id), BattleRecord{wins: 0, losses: 1});
+        assert_eq!(Balances::free_balance(winner), 10_000 + 100);
+        assert_eq!(Balances::free_balance(loser), 10_000 - 100);
+        assert_eq!(Balances::reserved_balance(winner) + Balances::reserved_balance(loser), 0);
+    });
+}
+
+#[test]
+fn leaderboards_track_the_top_kitties() {
+    new_test_ext().execute_with(|| {
+        let oldest = conjure_kitty(1);
+        Timestamp::set_timestamp(10);
+        let middle = conjure_kitty(2);
+        Timestamp::set_timestamp(20);
+        let youngest = conjure_kitty(3);
+        Timestamp::set_timestamp(30);
+        conjure_kitty(3);
+        Timestamp::set_timestamp(40);
+        conjure_kitty(3);
+
+        let ages: Vec<H256> = Substratekitties::age_leaders().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ages, vec![oldest, middle]);
+
+        let power = |id| Substratekitties::power_of(&id).unwrap();
+        assert_ok!(Substratekitties::boost(Origin::signed(3), youngest, 50 * 100));
+        assert_eq!(Substratekitties::power_leaders()[0], (youngest, power(youngest)));
+        assert_eq!(Substratekitties::power_leaders().len(), 2);
+
+        // The next oldest candidate takes the released kitty's place.
+        assert_ok!(Substratekitties::release(Origin::signed(1), oldest));
+        let ages: Vec<H256> = Substratekitties::age_leaders().into_iter().map(|(id, _)| id).collect();
+        assert_eq!(ages, vec![middle, youngest]);
+        assert!(Substratekitties::power_leaders().iter().all(|(id, _)| *id != oldest));
+
+        let mut dna = [0u8; 32];
+        dna[0] = 0xe0;
+        assert_eq!(genome::rarity(&dna, &Mutations::get()), 1);
+        assert_eq!(genome::rarity(&[0u8; 32], &Mutations::get()), 0);
+    });
+}
+
+#[test]
+fn tournaments_play_a_round_per_block_and_pay_the_finishers() {
+    new_test_ext().execute_with(|| {
+        let kitties = [conjure_kitty(1), conjure_kitty(1), conjure_kitty(2), conjure_kitty(3)];
+        assert_noop!(
+            Substratekitties::create_tournament(Origin::signed(1), 5, 100, 3),
+            Error::<Test>::BadTournamentSize
+        );
+        assert_ok!(Substratekitties::create_tournament(Origin::signed(1), 4, 100, 3));
+        for (owner, kitty) in [1, 1, 2, 3].iter().zip(kitties.iter()) {
+            assert_ok!(Substratekitties::enter_tournament(Origin::signed(*owner), 0, *kitty));
+        }
+        assert_noop!(
+            Substratekitties::enter_tournament(Origin::signed(1), 0, kitties[0]),
+            Error::<Test>::TournamentFull
+        );
+        let pool = Substratekitties::tournament_account(0);
+        assert_eq!(Balances::free_balance(pool), 400);
+
+        System::set_block_number(3);
+        Substratekitties::on_initialize(3);
+        assert_eq!(Substratekitties::bracket(0).round.len(), 2);
+        assert_eq!(Substratekitties::bracket(0).eliminated.len(), 2);
+
+        System::set_block_number(4);
+        Substratekitties::on_initialize(4);
+        assert_eq!(Substratekitties::tournament(0), None);
+        assert!(Substratekitties::running_tournaments().is_empty());
+        assert_eq!(Balances::free_balance(pool), 0);
+        assert_eq!(Balances::total_issuance(), 40_000);
+        let wins: u32 = kitties.iter().map(|kitty| Substratekitties::battle_record(kitty).wins).sum();
+        assert_eq!(wins, 3);
+    });
+}
+
+#[test]
+fn tournaments_without_enough_entrants_are_refunded() {
+    new_test_ext().execute_with(|| {
+        let kitty = conjure_kitty(1);
+        assert_ok!(Substratekitties::create_tournament(Origin::signed(2), 4, 100, 3));
+        assert_ok!(Substratekitties::create_tournament(Origin::signed(2), 4, 100, 3));
+        assert_noop!(
+            Substratekitties::create_tournament(Origin::signed(2), 4, 100, 3),
+            Error::<Test>::TooManyTournaments
+        );
+        assert_ok!(Substratekitties::enter_tournament(Origin::signed(1), 0, kitty));
+        assert_eq!(Balances::free_balance(1), 10_000 - 100);
+
+        System::set_block_number(3);
+        Substratekitties::on_initialize(3);
+        assert_eq!(Substratekitties::tournament(0), None);
+        assert_eq!(Balances::free_balance(1), 10_000);
+        assert_eq!(Substratekitties::open_tournaments(), 0);
+        assert_ok!(Substratekitties::create_tournament(Origin::signed(2), 4, 100, 4));
+    });
+}
@@ -17,7 +17,7 @@ use sp_runtime::traits::{
 use sp_runtime::{
     create_runtime_str, generic, impl_opaque_keys,
     transaction_validity::{TransactionSource, TransactionValidity},
-    ApplyExtrinsicResult, MultiSignature,
+    ApplyExtrinsicResult, ModuleId, MultiSignature,
 };
 use sp_std::{prelude::*, vec::Vec};
 #[cfg(feature = "std")]
@@ -41,6 +41,7 @@ pub use sp_runtime::BuildStorage;
 pub use sp_runtime::{Perbill, Permill};
 
 pub use pallet_commodities;
+use pallet_substratekitties::{Mutation, Slot};
 
 /// An index to a block.
 pub type BlockNumber = u32;
@@ -282,6 +283,43 @@ impl pallet_commodities::Trait for Runtime {
 
 parameter_types! {
     pub const BasePrice: Balance = 1 * DOLLARS;
+    pub const FlirtPeriod: BlockNumber = 1 * DAYS;
+    pub const MaxIntentPrunes: u32 = 64;
+    pub const MaxPedigreeDepth: u32 = 8;
+    pub const MaxInbreedingDepth: u32 = 3;
+    pub CooldownSchedule: Vec<BlockNumber> = vec![
+        1 * MINUTES,
+        5 * MINUTES,
+        30 * MINUTES,
+        2 * HOURS,
+        8 * HOURS,
+        1 * DAYS,
+        7 * DAYS,
+    ];
+    pub const MinBreedingAge: Moment = 60 * 60 * 1000;
+    pub const MaxAuctionSettlements: u32 = 32;
+    pub const OfferPeriod: BlockNumber = 7 * DAYS;
+    pub const MaxOfferRefunds: u32 = 64;
+    pub const MaxOffersPerKitty: u32 = 32;
+    pub const MaxRentalReturns: u32 = 64;
+    pub const MaxBattles: u32 = 64;
+    pub const LeaderboardSize: u32 = 100;
+    pub const MaxTournamentSize: u32 = 64;
+    pub const MaxRunningTournaments: u32 = 32;
+    pub const MaxTournamentMatches: u32 = 32;
+    pub TournamentPrizes: Vec<Perbill> = vec![
+        Perbill::from_percent(60),
+        Perbill::from_percent(30),
+        Perbill::from_percent(10),
+    ];
+    pub const RoyaltyPercent: Perbill = Perbill::from_percent(5);
+    pub const KittiesTreasuryId: ModuleId = ModuleId(*b"kit/trsy");
+    pub const KittiesTournamentId: ModuleId = ModuleId(*b"kit/trnm");
+    pub Mutations: Vec<Mutation> = vec![
+        Mutation { slot: Slot::Body, alleles: (3, 7), result: 14, chance: Perbill::from_percent(25) },
+        Mutation { slot: Slot::Eyes, alleles: (4, 9), result: 13, chance: Perbill::from_percent(20) },
+        Mutation { slot: Slot::Snack, alleles: (7, 9), result: 8, chance: Perbill::from_percent(5) },
+    ];
 }
 
 impl pallet_substratekitties::Trait for Runtime {
@@ -290,6 +328,27 @@ impl pallet_substratekitties::Trait for Runtime {
     type Randomness = pallet_randomness_collective_flip::Module<Runtime>;
     type Currency = pallet_balances::Module<Runtime>;
     type BasePrice = BasePrice;
+    type FlirtPeriod = FlirtPeriod;
+    type MaxIntentPrunes = MaxIntentPrunes;
+    type MaxPedigreeDepth = MaxPedigreeDepth;
+    type MaxInbreedingDepth = MaxInbreedingDepth;
+    type CooldownSchedule = CooldownSchedule;
+    type MinBreedingAge = MinBreedingAge;
+    type Mutations = Mutations;
+    type MaxAuctionSettlements = MaxAuctionSettlements;
+    type OfferPeriod = OfferPeriod;
+    type MaxOfferRefunds = MaxOfferRefunds;
+    type MaxOffersPerKitty = MaxOffersPerKitty;
+    type RoyaltyPercent = RoyaltyPercent;
+    type TreasuryId = KittiesTreasuryId;
+    type MaxRentalReturns = MaxRentalReturns;
+    type MaxBattles = MaxBattles;
+    type LeaderboardSize = LeaderboardSize;
+    type TournamentId = KittiesTournamentId;
+    type MaxTournamentSize = MaxTournamentSize;
+    type MaxRunningTournaments = MaxRunningTournaments;
+    type MaxTournamentMatches = MaxTournamentMatches;
+    type TournamentPrizes = TournamentPrizes;
     type Event = Event;
 }
 
@@ -307,7 +366,9 @@ construct_runtime!(
         Balances: pallet_balances::{Module, Call, Storage, Config<T>, Event<T>},
         TransactionPayment: pallet_transaction_payment::{Module, Storage},
         Sudo: pallet_sudo::{Module, Call, Config<T>, Storage, Event<T>},
-        KittiesCommodities: pallet_commodities::{Module, Call, Storage, Event<T>},
+        // Kitties change hands only through the substratekitties pallet, which moves their stake
+        // and listings with them, so the commodities pallet's own calls are not exposed.
+        KittiesCommodities: pallet_commodities::{Module, Storage, Event<T>},
         Substratekitties: pallet_substratekitties::{Module, Call, Storage, Event<T>},
     }
 );